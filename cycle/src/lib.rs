@@ -0,0 +1,165 @@
+//! Cycle detection for deterministic state machines.
+//!
+//! A machine is a start state plus a step function returning the next state,
+//! or `None` once the machine halts. Running forever means the sequence of
+//! states is `x0, x1, .. x(mu-1)` followed by a cycle `x(mu) .. x(mu+lambda-1)`
+//! repeating forever.
+//!
+//! `day-6 verify` checks the strategies against each other and a brute force
+//! walk.
+
+use std::{collections::HashMap, hash::Hash};
+
+pub struct Cycle<S> {
+    /// Number of states before the cycle starts (mu)
+    pub tail_len: usize,
+    /// Number of states in the cycle (lambda)
+    pub cycle_len: usize,
+    /// The first state that is visited twice, i.e. where the cycle starts
+    pub first_repeated: S,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Remember every state. One pass, O(mu + lambda) memory
+    HashSet,
+    /// Tortoise and hare. O(1) memory, about 3x the steps
+    Floyd,
+    /// Power-of-two teleporting tortoise. O(1) memory, fewer steps than Floyd
+    Brent,
+}
+
+/// Runs the machine with the given strategy, returning `None` if it halts.
+pub fn detect<S: Clone + Eq + Hash>(
+    start: S,
+    step: impl FnMut(&S) -> Option<S>,
+    strategy: Strategy,
+) -> Option<Cycle<S>> {
+    match strategy {
+        Strategy::HashSet => hash_set(start, step),
+        Strategy::Floyd => floyd(start, step),
+        Strategy::Brent => brent(start, step),
+    }
+}
+
+pub fn hash_set<S: Clone + Eq + Hash>(
+    start: S,
+    mut step: impl FnMut(&S) -> Option<S>,
+) -> Option<Cycle<S>> {
+    let mut first_seen: HashMap<S, usize> = HashMap::new();
+    let mut state = start;
+    let mut index = 0;
+    while !first_seen.contains_key(&state) {
+        let next = step(&state)?;
+        first_seen.insert(state, index);
+        state = next;
+        index += 1;
+    }
+
+    let tail_len = first_seen[&state];
+    Some(Cycle {
+        tail_len,
+        cycle_len: index - tail_len,
+        first_repeated: state,
+    })
+}
+
+pub fn floyd<S: Clone + Eq>(
+    start: S,
+    mut step: impl FnMut(&S) -> Option<S>,
+) -> Option<Cycle<S>> {
+    // Hare moves twice as fast, so they meet somewhere inside the cycle
+    let mut tortoise = step(&start)?;
+    let mut hare = step(&tortoise)?;
+    while tortoise != hare {
+        tortoise = step(&tortoise)?;
+        hare = step(&hare)?;
+        hare = step(&hare)?;
+    }
+
+    // The meeting point is a multiple of lambda from the start, so walking
+    // from the start and the meeting point in lockstep meets at x(mu)
+    let mut step = |state: &S| step(state).expect("Step function is not deterministic");
+    let mut tail_len = 0;
+    tortoise = start;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        tail_len += 1;
+    }
+
+    let cycle_len = cycle_len_from(&tortoise, &mut step);
+    Some(Cycle {
+        tail_len,
+        cycle_len,
+        first_repeated: tortoise,
+    })
+}
+
+pub fn brent<S: Clone + Eq>(
+    start: S,
+    mut step: impl FnMut(&S) -> Option<S>,
+) -> Option<Cycle<S>> {
+    // Find lambda directly by moving the tortoise up to the hare at each power of two
+    let mut power = 1;
+    let mut cycle_len = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(&start)?;
+    while tortoise != hare {
+        if power == cycle_len {
+            tortoise = hare.clone();
+            power *= 2;
+            cycle_len = 0;
+        }
+        hare = step(&hare)?;
+        cycle_len += 1;
+    }
+
+    // Then walk two pointers lambda apart from the start until they meet at x(mu)
+    let mut step = |state: &S| step(state).expect("Step function is not deterministic");
+    tortoise = start.clone();
+    hare = start;
+    for _ in 0..cycle_len {
+        hare = step(&hare);
+    }
+    let mut tail_len = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        tail_len += 1;
+    }
+
+    Some(Cycle {
+        tail_len,
+        cycle_len,
+        first_repeated: tortoise,
+    })
+}
+
+/// Runs the machine until `insert` reports a state was already seen, returning
+/// that state (the start of the cycle), or `None` if the machine halts.
+///
+/// Only answers whether there is a cycle, but lets the caller bring a cheaper
+/// set than hashing, e.g. a bitset over a bounded state space. `insert` returns
+/// whether the state was newly added.
+pub fn find_repeat<S>(
+    start: S,
+    mut step: impl FnMut(&S) -> Option<S>,
+    mut insert: impl FnMut(&S) -> bool,
+) -> Option<S> {
+    let mut state = start;
+    while insert(&state) {
+        state = step(&state)?;
+    }
+    Some(state)
+}
+
+fn cycle_len_from<S: Eq>(state: &S, step: &mut impl FnMut(&S) -> S) -> usize {
+    let mut cycle_len = 1;
+    let mut next = step(state);
+    while next != *state {
+        next = step(&next);
+        cycle_len += 1;
+    }
+    cycle_len
+}
@@ -0,0 +1,96 @@
+use std::{
+    hint,
+    io::{self, BufWriter, Write},
+    time::Instant,
+};
+
+use rng::Rng;
+
+use crate::{input, similarity_score};
+
+// Above this the O(n * m) version takes too long to be worth waiting for, so
+// it is only timed on a sample of list1 and scaled up
+const NAIVE_MAX_LINES: usize = 100_000;
+
+// The original part 2: scan all of list2 for every element of list1
+fn similarity_score_naive(list1: &[i64], list2: &[i64]) -> i128 {
+    list1
+        .iter()
+        .map(|num1| *num1 as i128 * list2.iter().filter(|num2| num1 == *num2).count() as i128)
+        .sum()
+}
+
+// `lines` lines of two 5 digit location ids, like the puzzle input
+pub fn generate_input(out: &mut impl Write, lines: usize, seed: u64) -> io::Result<()> {
+    let mut rng = Rng::new(seed);
+    for _ in 0..lines {
+        let num1 = 10_000 + rng.next_u64() % 90_000;
+        let num2 = 10_000 + rng.next_u64() % 90_000;
+        writeln!(out, "{}   {}", num1, num2)?;
+    }
+    Ok(())
+}
+
+// Writes LINES generated lines to stdout, for trying out huge inputs
+pub fn generate(mut args: impl Iterator<Item = String>) {
+    let lines: usize = args
+        .next()
+        .expect("Missing LINES")
+        .parse()
+        .expect("LINES must be a number");
+    let seed: u64 = args
+        .next()
+        .map_or(1, |arg| arg.parse().expect("SEED must be a number"));
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    generate_input(&mut out, lines, seed)
+        .and_then(|_| out.flush())
+        .expect("Failed to write to stdout");
+}
+
+// Times both similarity score implementations on generated inputs of
+// increasing size, up to MAX_LINES (default 1,000,000). Naive times marked ~
+// are estimated, unless `--full-naive` is given
+pub fn run(args: impl Iterator<Item = String>) {
+    let mut max_lines = 1_000_000;
+    let mut full_naive = false;
+    for arg in args {
+        match arg.as_str() {
+            "--full-naive" => full_naive = true,
+            _ => max_lines = arg.parse().expect("MAX_LINES must be a number"),
+        }
+    }
+
+    println!("{:>10} {:>14} {:>14}", "lines", "linear", "naive");
+    let mut lines = 1_000;
+    while lines <= max_lines {
+        let mut input = Vec::with_capacity(lines * 14);
+        generate_input(&mut input, lines, lines as u64).unwrap();
+        let (list1, list2) = input::read_lists::<i64, _>(&input[..]).unwrap();
+
+        let now = Instant::now();
+        let linear = similarity_score(&list1, &list2).unwrap();
+        let linear_elapsed = now.elapsed();
+
+        let naive_elapsed = if lines <= NAIVE_MAX_LINES || full_naive {
+            let now = Instant::now();
+            let naive = similarity_score_naive(&list1, &list2);
+            let elapsed = now.elapsed();
+            assert_eq!(linear, naive);
+            format!("{:?}", elapsed)
+        } else {
+            // About as much work as the largest full run
+            let sample = NAIVE_MAX_LINES * NAIVE_MAX_LINES / lines;
+            let now = Instant::now();
+            hint::black_box(similarity_score_naive(&list1[..sample], &list2));
+            let elapsed = now.elapsed();
+            format!("~{:?}", elapsed.mul_f64(lines as f64 / sample as f64))
+        };
+
+        println!(
+            "{:>10} {:>14?} {:>14}",
+            lines, linear_elapsed, naive_elapsed
+        );
+        lines *= 10;
+    }
+}
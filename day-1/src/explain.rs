@@ -0,0 +1,157 @@
+//! Per-row breakdown of both parts, for tracking down wrong answers.
+
+use std::{
+    fs::File,
+    io::{self, BufReader, Write},
+    iter::zip,
+};
+
+use crate::{histogram, input, similarity_score, total_distance, Error, LocationId};
+
+pub enum Format {
+    Text,
+    Csv,
+}
+
+struct Explanation {
+    headers: [&'static str; 4],
+    // The last column is each row's contribution to the total
+    rows: Vec<[i128; 4]>,
+    total: i128,
+}
+
+impl Explanation {
+    // Each pair of the sorted lists and its distance
+    fn part_1<T: LocationId>(list1: &[T], list2: &[T]) -> Result<Explanation, Error> {
+        let mut list1 = list1.to_vec();
+        let mut list2 = list2.to_vec();
+        list1.sort();
+        list2.sort();
+
+        let rows = zip(&list1, &list2)
+            .enumerate()
+            .map(|(rank, (num1, num2))| {
+                let (num1, num2) = ((*num1).into(), (*num2).into());
+                let distance = i128::checked_sub(num2, num1)
+                    .and_then(i128::checked_abs)
+                    .ok_or(Error::Overflow)?;
+                Ok([rank as i128 + 1, num1, num2, distance])
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Explanation {
+            headers: ["rank", "left", "right", "distance"],
+            rows,
+            total: total_distance(&list1, &list2)?,
+        })
+    }
+
+    // Each left value, how often it appears on the right, and their product
+    fn part_2<T: LocationId>(list1: &[T], list2: &[T]) -> Result<Explanation, Error> {
+        let counts = histogram(list2);
+        let rows = list1
+            .iter()
+            .enumerate()
+            .map(|(index, num1)| {
+                let count = counts.get(num1).copied().unwrap_or(0) as i128;
+                let contribution = (*num1).into().checked_mul(count).ok_or(Error::Overflow)?;
+                Ok([index as i128 + 1, (*num1).into(), count, contribution])
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Explanation {
+            headers: ["line", "left", "count", "contribution"],
+            rows,
+            total: similarity_score(list1, list2)?,
+        })
+    }
+
+    // Keeps the `k` rows contributing the most, largest first
+    fn top(&mut self, k: usize) {
+        self.rows.sort_by_key(|row| std::cmp::Reverse(row[3]));
+        self.rows.truncate(k);
+    }
+
+    fn write(&self, out: &mut impl Write, format: &Format) -> io::Result<()> {
+        match format {
+            Format::Csv => {
+                writeln!(out, "{}", self.headers.join(","))?;
+                for row in &self.rows {
+                    writeln!(out, "{},{},{},{}", row[0], row[1], row[2], row[3])?;
+                }
+            }
+            Format::Text => {
+                let width = self
+                    .rows
+                    .iter()
+                    .flatten()
+                    .chain([&self.total])
+                    .map(|value| value.to_string().len())
+                    .chain(self.headers.iter().map(|header| header.len()))
+                    .max()
+                    .unwrap_or(0);
+                let line: Vec<String> = self
+                    .headers
+                    .iter()
+                    .map(|header| format!("{:>width$}", header))
+                    .collect();
+                writeln!(out, "{}", line.join(" "))?;
+                for row in &self.rows {
+                    let line: Vec<String> = row
+                        .iter()
+                        .map(|value| format!("{:>width$}", value))
+                        .collect();
+                    writeln!(out, "{}", line.join(" "))?;
+                }
+                writeln!(out, "total {}", self.total)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// `explain [--part 1|2] [--top K] [--format text|csv] [FILE]`
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<(), Error> {
+    let mut part = 1;
+    let mut top = None;
+    let mut format = Format::Text;
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .unwrap_or_else(|| panic!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--part" => {
+                part = match value().as_str() {
+                    "1" => 1,
+                    "2" => 2,
+                    _ => panic!("--part must be 1 or 2"),
+                }
+            }
+            "--top" => top = Some(value().parse().expect("--top must be a number")),
+            "--format" => {
+                format = match value().as_str() {
+                    "text" => Format::Text,
+                    "csv" => Format::Csv,
+                    _ => panic!("--format must be text or csv"),
+                }
+            }
+            _ => path = Some(arg),
+        }
+    }
+
+    let path = path.unwrap_or(format!("input_{}.txt", part));
+    let file = File::open(&path).expect("File not found");
+    let (list1, list2) = input::read_lists::<i64, _>(BufReader::new(file))?;
+
+    let mut explanation = match part {
+        1 => Explanation::part_1(&list1, &list2)?,
+        _ => Explanation::part_2(&list1, &list2)?,
+    };
+    if let Some(k) = top {
+        explanation.top(k);
+    }
+    explanation.write(&mut io::stdout().lock(), &format)?;
+    Ok(())
+}
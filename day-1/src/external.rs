@@ -0,0 +1,199 @@
+//! Part 1 for inputs too big to hold in memory: each column is split into
+//! sorted runs spilled to temporary files, which are then merged back in order.
+
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    env,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{input, Error, LocationId};
+
+// Values held in memory per column before a run is spilled
+const DEFAULT_RUN_LEN: usize = 1 << 20;
+
+// A directory under the system temp dir, removed with everything in it on drop
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> io::Result<TempDir> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = env::temp_dir().join(format!(
+            "day-1-{}-{}",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&path)?;
+        Ok(TempDir(path))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+// Buffers one column, spilling it as a sorted run whenever the buffer fills up
+struct RunWriter<'a, T> {
+    dir: &'a TempDir,
+    name: String,
+    buffer: Vec<T>,
+    run_len: usize,
+    runs: Vec<PathBuf>,
+}
+
+impl<'a, T: LocationId> RunWriter<'a, T> {
+    fn new(dir: &'a TempDir, name: String, run_len: usize) -> RunWriter<'a, T> {
+        RunWriter {
+            dir,
+            name,
+            buffer: Vec::with_capacity(run_len),
+            run_len,
+            runs: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, value: T) -> io::Result<()> {
+        self.buffer.push(value);
+        if self.buffer.len() >= self.run_len {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    fn spill(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        self.buffer.sort_unstable();
+
+        let path = self
+            .dir
+            .0
+            .join(format!("{}-{}", self.name, self.runs.len()));
+        let mut out = BufWriter::new(File::create(&path)?);
+        for value in self.buffer.drain(..) {
+            writeln!(out, "{}", value)?;
+        }
+        out.flush()?;
+        self.runs.push(path);
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<MergedRuns<T>> {
+        self.spill()?;
+        MergedRuns::open(&self.runs)
+    }
+}
+
+// Yields the values of several sorted runs in sorted order
+struct MergedRuns<T> {
+    readers: Vec<BufReader<File>>,
+    // Smallest unread value of each run, tagged with its run's index
+    heap: BinaryHeap<Reverse<(T, usize)>>,
+    line: String,
+}
+
+impl<T: LocationId> MergedRuns<T> {
+    fn open(runs: &[PathBuf]) -> io::Result<MergedRuns<T>> {
+        let mut merged = MergedRuns {
+            readers: Vec::with_capacity(runs.len()),
+            heap: BinaryHeap::with_capacity(runs.len()),
+            line: String::new(),
+        };
+        for path in runs {
+            merged.readers.push(BufReader::new(File::open(path)?));
+            merged.refill(merged.readers.len() - 1)?;
+        }
+        Ok(merged)
+    }
+
+    // Pushes the next value of run `run` onto the heap, if it has one
+    fn refill(&mut self, run: usize) -> io::Result<()> {
+        self.line.clear();
+        if self.readers[run].read_line(&mut self.line)? == 0 {
+            return Ok(());
+        }
+        let value = self
+            .line
+            .trim_end()
+            .parse()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Corrupt sorted run"))?;
+        self.heap.push(Reverse((value, run)));
+        Ok(())
+    }
+
+    fn next(&mut self) -> io::Result<Option<T>> {
+        let Some(Reverse((value, run))) = self.heap.pop() else {
+            return Ok(None);
+        };
+        self.refill(run)?;
+        Ok(Some(value))
+    }
+}
+
+// Part 1 holding at most `run_len` values per column in memory at a time
+pub fn total_distance_external<T: LocationId, R: BufRead>(
+    reader: R,
+    run_len: usize,
+) -> Result<i128, Error> {
+    let dir = TempDir::new()?;
+    let mut writers: Vec<RunWriter<T>> = Vec::new();
+    let num_columns = input::for_each_row(reader, |values: &[T]| {
+        if writers.is_empty() {
+            writers = (0..values.len())
+                .map(|column| RunWriter::new(&dir, format!("column-{}", column), run_len))
+                .collect();
+        }
+        for (writer, value) in writers.iter_mut().zip(values) {
+            writer.push(*value)?;
+        }
+        Ok(())
+    })?;
+    if num_columns != 2 {
+        return Err(Error::ColumnCount(num_columns));
+    }
+
+    let mut writers = writers.into_iter();
+    let mut list1 = writers.next().unwrap().finish()?;
+    let mut list2 = writers.next().unwrap().finish()?;
+
+    // Same as `total_distance`, pairing values as they come off the merges
+    let mut total: i128 = 0;
+    while let (Some(num1), Some(num2)) = (list1.next()?, list2.next()?) {
+        let distance = num2
+            .into()
+            .checked_sub(num1.into())
+            .and_then(i128::checked_abs)
+            .ok_or(Error::Overflow)?;
+        total = total.checked_add(distance).ok_or(Error::Overflow)?;
+    }
+    Ok(total)
+}
+
+// `external [--run-len N] [FILE]`
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<i128, Error> {
+    let mut run_len = DEFAULT_RUN_LEN;
+    let mut path = "input_1.txt".to_string();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--run-len" => {
+                run_len = args
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .filter(|run_len| *run_len > 0)
+                    .expect("--run-len needs a positive number");
+            }
+            _ => path = arg,
+        }
+    }
+
+    let file = File::open(&path).expect("File not found");
+    total_distance_external::<i64, _>(BufReader::new(file), run_len)
+}
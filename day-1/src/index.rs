@@ -0,0 +1,266 @@
+//! Both lists kept sorted, with their total distance and similarity score
+//! updated on every edit instead of recomputed, for "what if" queries.
+//!
+//! Nothing is ever re-sorted, and the similarity score is O(1) to update. But
+//! inserting or removing a value gives every pair ranked after it a new
+//! partner, so those edits still cost O(n) for the distance, the same as
+//! recomputing it from the sorted lists. Only `replace` is cheaper.
+
+use std::{collections::HashMap, fs::File, io::BufReader};
+
+use crate::{histogram, input, similarity_score, total_distance, Error, LocationId};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+pub struct ListIndex<T> {
+    // Sorted, so a value's rank is a binary search away
+    left: Vec<T>,
+    right: Vec<T>,
+    left_counts: HashMap<T, usize>,
+    right_counts: HashMap<T, usize>,
+    distance: i128,
+    similarity: i128,
+}
+
+impl<T: LocationId> ListIndex<T> {
+    pub fn new(mut left: Vec<T>, mut right: Vec<T>) -> Result<ListIndex<T>, Error> {
+        left.sort();
+        right.sort();
+        Ok(ListIndex {
+            distance: total_distance(&left, &right)?,
+            similarity: similarity_score(&left, &right)?,
+            left_counts: histogram(&left),
+            right_counts: histogram(&right),
+            left,
+            right,
+        })
+    }
+
+    /// Same as part 1: the distance between sorted pairs, ignoring any values
+    /// past the end of the shorter list
+    pub fn distance(&self) -> i128 {
+        self.distance
+    }
+
+    /// Same as part 2
+    pub fn similarity(&self) -> i128 {
+        self.similarity
+    }
+
+    fn list(&self, side: Side) -> &Vec<T> {
+        match side {
+            Side::Left => &self.left,
+            Side::Right => &self.right,
+        }
+    }
+
+    fn list_mut(&mut self, side: Side) -> &mut Vec<T> {
+        match side {
+            Side::Left => &mut self.left,
+            Side::Right => &mut self.right,
+        }
+    }
+
+    fn counts_mut(&mut self, side: Side) -> &mut HashMap<T, usize> {
+        match side {
+            Side::Left => &mut self.left_counts,
+            Side::Right => &mut self.right_counts,
+        }
+    }
+
+    fn other_count(&self, side: Side, value: T) -> usize {
+        let counts = match side {
+            Side::Left => &self.right_counts,
+            Side::Right => &self.left_counts,
+        };
+        counts.get(&value).copied().unwrap_or(0)
+    }
+
+    fn num_pairs(&self) -> usize {
+        self.left.len().min(self.right.len())
+    }
+
+    // Distance of the pairs with ranks in `start..end`
+    fn range_distance(&self, start: usize, end: usize) -> Result<i128, Error> {
+        let end = end.min(self.num_pairs());
+        total_distance(
+            &self.left[start.min(end)..end],
+            &self.right[start.min(end)..end],
+        )
+    }
+
+    // Runs `edit`, which may only change pairs with ranks from `start` up to
+    // `end` (clamped to the pairs before and after), fixing up the distance
+    fn update_distance(
+        &mut self,
+        start: usize,
+        end: usize,
+        edit: impl FnOnce(&mut Self),
+    ) -> Result<(), Error> {
+        let before = self.range_distance(start, end)?;
+        edit(self);
+        let after = self.range_distance(start, end)?;
+        self.distance = (self.distance - before)
+            .checked_add(after)
+            .ok_or(Error::Overflow)?;
+        Ok(())
+    }
+
+    // Adds `sign` times the value's contribution to the similarity score,
+    // for the value being added to or removed from `side`
+    fn update_similarity(&mut self, side: Side, value: T, sign: i128) -> Result<(), Error> {
+        let contribution = value
+            .into()
+            .checked_mul(self.other_count(side, value) as i128 * sign)
+            .ok_or(Error::Overflow)?;
+        self.similarity = self
+            .similarity
+            .checked_add(contribution)
+            .ok_or(Error::Overflow)?;
+        Ok(())
+    }
+
+    /// O(log n) to find the rank `r` of `value`, then O(n - r) to shift every
+    /// value after it and recompute the distance of their pairs
+    pub fn insert(&mut self, side: Side, value: T) -> Result<(), Error> {
+        let rank = self.list(side).partition_point(|other| *other < value);
+        self.update_distance(rank, usize::MAX, |index| {
+            index.list_mut(side).insert(rank, value);
+        })?;
+        *self.counts_mut(side).entry(value).or_insert(0) += 1;
+        self.update_similarity(side, value, 1)
+    }
+
+    /// Removes one occurrence of `value`, returning false if there is none.
+    /// Same cost as `insert`
+    pub fn remove(&mut self, side: Side, value: T) -> Result<bool, Error> {
+        let Ok(rank) = self.list(side).binary_search(&value) else {
+            return Ok(false);
+        };
+        self.update_distance(rank, usize::MAX, |index| {
+            index.list_mut(side).remove(rank);
+        })?;
+        self.update_similarity(side, value, -1)?;
+        let counts = self.counts_mut(side);
+        *counts.get_mut(&value).unwrap() -= 1;
+        if counts[&value] == 0 {
+            counts.remove(&value);
+        }
+        Ok(true)
+    }
+
+    /// Changes one occurrence of `old` into `new`, returning false if there is
+    /// no `old`. Only the values ranked between the two move, each one place,
+    /// so this is O(log n + the number of values between them)
+    pub fn replace(&mut self, side: Side, old: T, new: T) -> Result<bool, Error> {
+        let Ok(old_rank) = self.list(side).binary_search(&old) else {
+            return Ok(false);
+        };
+        // Rank `new` ends up at once `old` is gone
+        let insert_rank = self.list(side).partition_point(|other| *other < new);
+        let new_rank = if insert_rank > old_rank {
+            insert_rank - 1
+        } else {
+            insert_rank
+        };
+
+        let (start, end) = (old_rank.min(new_rank), old_rank.max(new_rank) + 1);
+        self.update_distance(start, end, |index| {
+            // Rotating just the values in between rather than removing and
+            // inserting, which would shift everything after them too
+            let list = index.list_mut(side);
+            list[old_rank] = new;
+            if new_rank > old_rank {
+                list[old_rank..=new_rank].rotate_left(1);
+            } else {
+                list[new_rank..=old_rank].rotate_right(1);
+            }
+        })?;
+
+        self.update_similarity(side, old, -1)?;
+        let counts = self.counts_mut(side);
+        *counts.get_mut(&old).unwrap() -= 1;
+        if counts[&old] == 0 {
+            counts.remove(&old);
+        }
+        *counts.entry(new).or_insert(0) += 1;
+        self.update_similarity(side, new, 1)?;
+        Ok(true)
+    }
+}
+
+enum Edit<T> {
+    Insert(Side, T),
+    Remove(Side, T),
+    Replace(Side, T, T),
+}
+
+// `L+5` inserts 5 on the left, `R-5` removes a 5 from the right and `L5=7`
+// changes a 5 on the left into a 7
+fn parse_edit<T: LocationId>(edit: &str) -> Option<Edit<T>> {
+    let side = match edit.get(..1)? {
+        "L" | "l" => Side::Left,
+        "R" | "r" => Side::Right,
+        _ => return None,
+    };
+    let rest = &edit[1..];
+    if let Some(value) = rest.strip_prefix('+') {
+        Some(Edit::Insert(side, value.parse().ok()?))
+    } else if let Some(value) = rest.strip_prefix('-') {
+        Some(Edit::Remove(side, value.parse().ok()?))
+    } else {
+        let (old, new) = rest.split_once('=')?;
+        Some(Edit::Replace(side, old.parse().ok()?, new.parse().ok()?))
+    }
+}
+
+// `what-if [FILE] EDIT...`, applying each edit to the lists from FILE in turn.
+// Anything that isn't an edit is taken to be the file, so it can go anywhere
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), Error> {
+    let mut path = None;
+    let mut edits = Vec::new();
+    for arg in args {
+        match parse_edit::<i64>(&arg) {
+            Some(edit) => edits.push((arg, edit)),
+            None if path.is_none() => path = Some(arg),
+            None => panic!("Invalid edit {:?}, expected e.g. L+5, R-5 or L5=7", arg),
+        }
+    }
+
+    let file = File::open(path.as_deref().unwrap_or("input_1.txt")).expect("File not found");
+    let (list1, list2) = input::read_lists::<i64, _>(BufReader::new(file))?;
+    let mut index = ListIndex::new(list1, list2)?;
+
+    println!("{:<12} {:>16} {:>16}", "edit", "distance", "similarity");
+    println!(
+        "{:<12} {:>16} {:>16}",
+        "(none)",
+        index.distance(),
+        index.similarity()
+    );
+    for (arg, edit) in edits {
+        let applied = match edit {
+            Edit::Insert(side, value) => {
+                index.insert(side, value)?;
+                true
+            }
+            Edit::Remove(side, value) => index.remove(side, value)?,
+            Edit::Replace(side, old, new) => index.replace(side, old, new)?,
+        };
+        if applied {
+            println!(
+                "{:<12} {:>16} {:>16}",
+                arg,
+                index.distance(),
+                index.similarity()
+            );
+        } else {
+            println!("{:<12} {:>16}", arg, "(value not found)");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use std::{io::BufRead, iter::zip};
+
+use parsing::{Cursor, Separator};
+
+use crate::{Error, LocationId};
+
+// Calls `row` with the values on each non-blank line, checking that every line
+// has the same number of whitespace separated columns. Only one line is held in
+// memory at a time. Returns the number of columns
+pub fn for_each_row<T: LocationId, R: BufRead>(
+    mut reader: R,
+    mut row: impl FnMut(&[T]) -> Result<(), Error>,
+) -> Result<usize, Error> {
+    let mut line = String::new();
+    let mut line_number = 0;
+    let mut num_columns = None;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(num_columns.unwrap_or(0));
+        }
+        line_number += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let values = parsing::integer_list::<T>(&mut Cursor::new(&line), Separator::Whitespace)
+            .map_err(|err| err.at_line(line_number))?;
+        match num_columns {
+            None => num_columns = Some(values.len()),
+            Some(num_columns) if values.len() != num_columns => {
+                let err = Cursor::new(&line).error(format!(
+                    "expected {} columns, found {}",
+                    num_columns,
+                    values.len()
+                ));
+                return Err(err.at_line(line_number).into());
+            }
+            Some(_) => {}
+        }
+        row(&values)?;
+    }
+}
+
+pub fn read_columns<T: LocationId, R: BufRead>(reader: R) -> Result<Vec<Vec<T>>, Error> {
+    let mut columns: Vec<Vec<T>> = Vec::new();
+    for_each_row(reader, |values: &[T]| {
+        if columns.is_empty() {
+            columns = vec![Vec::new(); values.len()];
+        }
+        zip(&mut columns, values).for_each(|(column, value)| column.push(*value));
+        Ok(())
+    })?;
+    Ok(columns)
+}
+
+pub fn read_lists<T: LocationId, R: BufRead>(reader: R) -> Result<(Vec<T>, Vec<T>), Error> {
+    let columns = read_columns(reader)?;
+    match <[Vec<T>; 2]>::try_from(columns) {
+        Ok([list1, list2]) => Ok((list1, list2)),
+        Err(columns) => Err(Error::ColumnCount(columns.len())),
+    }
+}
@@ -1,45 +1,210 @@
-use std::{fs::File, io::Read, iter::zip};
+mod bench;
+mod explain;
+mod external;
+mod index;
+mod input;
+mod metrics;
+
+use std::{
+    collections::HashMap,
+    env,
+    fmt::{self, Display},
+    fs::File,
+    hash::Hash,
+    io::{self, BufReader},
+    iter::zip,
+    process,
+};
+
+use parsing::{Integer, ParseError};
 
 fn main() {
-    println!("{}", part_1());
-    println!("{}", part_2());
+    let mut args = env::args().skip(1);
+    if let Some(command) = args.next() {
+        match command.as_str() {
+            "bench" => bench::run(args),
+            "generate" => bench::generate(args),
+            "explain" => explain::run(args).unwrap_or_else(|err| exit_with(err)),
+            "what-if" => index::run(args).unwrap_or_else(|err| exit_with(err)),
+            "metrics" => metrics::run(args).unwrap_or_else(|err| exit_with(err)),
+            "matrix" => matrix(args.next().as_deref().unwrap_or("input_1.txt"))
+                .unwrap_or_else(|err| exit_with(err)),
+            "external" => {
+                let total = external::run(args).unwrap_or_else(|err| exit_with(err));
+                println!("{}", total);
+            }
+            _ => {
+                eprintln!(
+                    "Usage: day-1 [bench [--full-naive] [MAX_LINES] | generate LINES [SEED] | matrix [FILE] \
+                     | external [--run-len N] [FILE] \
+                     | explain [--part 1|2] [--top K] [--format text|csv] [FILE] \
+                     | what-if [FILE] EDIT... | metrics [--metric NAME]... [FILE]]"
+                );
+                process::exit(2);
+            }
+        }
+        return;
+    }
+
+    println!("{}", part_1().unwrap_or_else(|err| exit_with(err)));
+    println!("{}", part_2().unwrap_or_else(|err| exit_with(err)));
+}
+
+fn exit_with(err: Error) -> ! {
+    eprintln!("{}", err);
+    process::exit(1);
+}
+
+// Any primitive integer that widens losslessly into an i128, which is what
+// distances and scores are accumulated in
+trait LocationId: Integer + Ord + Hash + Display + Into<i128> {}
+
+impl<T: Integer + Ord + Hash + Display + Into<i128>> LocationId for T {}
+
+#[derive(Debug)]
+enum Error {
+    Io(io::Error),
+    Parse(ParseError),
+    // Number of columns found where exactly 2 were needed
+    ColumnCount(usize),
+    Overflow,
 }
 
-fn get_lists(buf: &str) -> (Vec<i32>, Vec<i32>) {
-    let result = buf.split_whitespace().fold(
-        /* Alternate adding to each list */
-        (Vec::new(), Vec::new(), true),
-        |(mut list1, mut list2, add_to_first), element| {
-            let to_add_to = if add_to_first { &mut list1 } else { &mut list2 };
-            to_add_to.push(element.parse::<i32>().unwrap());
-            (list1, list2, !add_to_first)
-        },
-    );
-    (result.0, result.1)
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{}", err),
+            Error::Parse(err) => write!(f, "Invalid input at {}", err),
+            Error::ColumnCount(found) => write!(f, "Expected 2 columns, found {}", found),
+            Error::Overflow => write!(f, "Arithmetic overflowed an i128"),
+        }
+    }
 }
 
-fn part_1() -> i32 {
-    let mut file = File::open("input_1.txt").expect("File not found");
-    let mut buf = String::new();
-    file.read_to_string(&mut buf).unwrap();
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
 
-    let (mut list1, mut list2) = get_lists(&buf);
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::Parse(err)
+    }
+}
+
+fn part_1() -> Result<i128, Error> {
+    let file = File::open("input_1.txt").expect("File not found");
+
+    let (mut list1, mut list2) = input::read_lists::<i64, _>(BufReader::new(file))?;
     list1.sort();
     list2.sort();
+    total_distance(&list1, &list2)
+}
 
+fn part_2() -> Result<i128, Error> {
+    let file = File::open("input_2.txt").expect("File not found");
+
+    let (list1, list2) = input::read_lists::<i64, _>(BufReader::new(file))?;
+    similarity_score(&list1, &list2)
+}
+
+// Sum of the distances between the pairs of (already sorted) lists
+fn total_distance<T: LocationId>(list1: &[T], list2: &[T]) -> Result<i128, Error> {
     zip(list1, list2)
-        .map(|(num1, num2)| (num2 - num1).abs())
-        .sum()
+        .try_fold(0_i128, |total, (num1, num2)| {
+            let distance = (*num2).into().checked_sub((*num1).into())?.checked_abs()?;
+            total.checked_add(distance)
+        })
+        .ok_or(Error::Overflow)
+}
+
+// How many times each value appears
+fn histogram<T: Copy + Eq + Hash>(list: &[T]) -> HashMap<T, usize> {
+    let mut counts = HashMap::new();
+    for num in list {
+        *counts.entry(*num).or_insert(0) += 1;
+    }
+    counts
+}
+
+// Sum of each number in list1 times how often it appears in list2, in O(n + m)
+fn similarity_score<T: LocationId>(list1: &[T], list2: &[T]) -> Result<i128, Error> {
+    let counts = histogram(list2);
+    list1
+        .iter()
+        .try_fold(0_i128, |total, num1| {
+            let count = counts.get(num1).copied().unwrap_or(0);
+            let contribution = (*num1).into().checked_mul(i128::try_from(count).ok()?)?;
+            total.checked_add(contribution)
+        })
+        .ok_or(Error::Overflow)
 }
 
-fn part_2() -> i32 {
-    let mut file = File::open("input_2.txt").expect("File not found");
-    let mut buf = String::new();
-    file.read_to_string(&mut buf).unwrap();
+// Entry [a][b] is the total distance between sorted columns a and b
+fn distance_matrix<T: LocationId>(columns: &[Vec<T>]) -> Result<Vec<Vec<i128>>, Error> {
+    let sorted: Vec<Vec<T>> = columns
+        .iter()
+        .map(|column| {
+            let mut column = column.clone();
+            column.sort();
+            column
+        })
+        .collect();
+    sorted
+        .iter()
+        .map(|column_a| {
+            sorted
+                .iter()
+                .map(|column_b| total_distance(column_a, column_b))
+                .collect()
+        })
+        .collect()
+}
+
+// Entry [a][b] is the similarity score of column a against column b, which
+// isn't symmetric
+fn similarity_matrix<T: LocationId>(columns: &[Vec<T>]) -> Result<Vec<Vec<i128>>, Error> {
+    columns
+        .iter()
+        .map(|column_a| {
+            columns
+                .iter()
+                .map(|column_b| similarity_score(column_a, column_b))
+                .collect()
+        })
+        .collect()
+}
+
+fn matrix(path: &str) -> Result<(), Error> {
+    let file = File::open(path).expect("File not found");
+
+    let columns = input::read_columns::<i64, _>(BufReader::new(file))?;
+    print_matrix("Distance", &distance_matrix(&columns)?);
+    println!();
+    print_matrix("Similarity", &similarity_matrix(&columns)?);
+    Ok(())
+}
 
-    let (list1, list2) = get_lists(&buf);
+fn print_matrix(title: &str, matrix: &[Vec<i128>]) {
+    let width = matrix
+        .iter()
+        .flatten()
+        .map(|value| value.to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max(title.len());
 
-    list1.iter().map(|num1| {
-        num1 * list2.iter().filter(|num2| num1 == *num2).count() as i32
-    }).sum()
+    print!("{:>width$}", title);
+    for column in 1..=matrix.len() {
+        print!(" {:>width$}", format!("col {}", column));
+    }
+    println!();
+    for (row, values) in matrix.iter().enumerate() {
+        print!("{:>width$}", format!("col {}", row + 1));
+        for value in values {
+            print!(" {:>width$}", value);
+        }
+        println!();
+    }
 }
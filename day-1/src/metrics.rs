@@ -0,0 +1,295 @@
+//! Other ways of comparing the two lists, beyond parts 1 and 2.
+
+use std::{collections::HashSet, fs::File, io::BufReader, iter::zip};
+
+use crate::{histogram, input, Error, LocationId};
+
+const METRICS: [&str; 7] = [
+    "squared-distance",
+    "max-gap",
+    "jaccard",
+    "overlap",
+    "spearman",
+    "kendall",
+    "summary",
+];
+
+fn sorted<T: LocationId>(list: &[T]) -> Vec<T> {
+    let mut list = list.to_vec();
+    list.sort();
+    list
+}
+
+// Sum of the squared distances between sorted pairs
+pub fn squared_distance<T: LocationId>(list1: &[T], list2: &[T]) -> Result<i128, Error> {
+    zip(sorted(list1), sorted(list2))
+        .try_fold(0_i128, |total, (num1, num2)| {
+            let distance = num2.into().checked_sub(num1.into())?;
+            total.checked_add(distance.checked_mul(distance)?)
+        })
+        .ok_or(Error::Overflow)
+}
+
+// Largest distance between sorted pairs
+pub fn max_gap<T: LocationId>(list1: &[T], list2: &[T]) -> Result<Option<i128>, Error> {
+    zip(sorted(list1), sorted(list2)).try_fold(None, |max: Option<i128>, (num1, num2)| {
+        let distance = num2
+            .into()
+            .checked_sub(num1.into())
+            .and_then(i128::checked_abs)
+            .ok_or(Error::Overflow)?;
+        Ok(Some(max.map_or(distance, |max| max.max(distance))))
+    })
+}
+
+// Distinct values in both lists over distinct values in either
+pub fn jaccard<T: LocationId>(list1: &[T], list2: &[T]) -> f64 {
+    let set1: HashSet<T> = list1.iter().copied().collect();
+    let set2: HashSet<T> = list2.iter().copied().collect();
+    let union = set1.union(&set2).count();
+    if union == 0 {
+        return 1.0;
+    }
+    set1.intersection(&set2).count() as f64 / union as f64
+}
+
+// Values the lists have in common counting repeats (sum of the smaller count of
+// each value), and that over the sum of the larger counts
+pub fn multiset_overlap<T: LocationId>(list1: &[T], list2: &[T]) -> (usize, f64) {
+    let counts1 = histogram(list1);
+    let counts2 = histogram(list2);
+    let values: HashSet<&T> = counts1.keys().chain(counts2.keys()).collect();
+
+    let (mut shared, mut total) = (0, 0);
+    for value in values {
+        let count1 = counts1.get(value).copied().unwrap_or(0);
+        let count2 = counts2.get(value).copied().unwrap_or(0);
+        shared += count1.min(count2);
+        total += count1.max(count2);
+    }
+    let ratio = if total == 0 {
+        1.0
+    } else {
+        shared as f64 / total as f64
+    };
+    (shared, ratio)
+}
+
+// 1-based ranks, with tied values sharing the average of their ranks
+fn ranks<T: LocationId>(list: &[T]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..list.len()).collect();
+    order.sort_by_key(|&i| list[i]);
+
+    let mut ranks = vec![0.0; list.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let end = start
+            + order[start..]
+                .iter()
+                .take_while(|&&i| list[i] == list[order[start]])
+                .count();
+        let average = (start + end + 1) as f64 / 2.0;
+        order[start..end].iter().for_each(|&i| ranks[i] = average);
+        start = end;
+    }
+    ranks
+}
+
+fn pearson(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    let n = xs.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let mean_y = ys.iter().sum::<f64>() / n;
+    let (mut covariance, mut variance_x, mut variance_y) = (0.0, 0.0, 0.0);
+    for (x, y) in zip(xs, ys) {
+        covariance += (x - mean_x) * (y - mean_y);
+        variance_x += (x - mean_x) * (x - mean_x);
+        variance_y += (y - mean_y) * (y - mean_y);
+    }
+    let denominator = (variance_x * variance_y).sqrt();
+    (denominator > 0.0).then(|| covariance / denominator)
+}
+
+// Spearman's rho between the columns, pairing values on the same line. None if
+// either column is constant
+pub fn spearman<T: LocationId>(list1: &[T], list2: &[T]) -> Option<f64> {
+    let len = list1.len().min(list2.len());
+    if len < 2 {
+        return None;
+    }
+    pearson(&ranks(&list1[..len]), &ranks(&list2[..len]))
+}
+
+// Number of pairs within each run of equal values in a sorted list
+fn tied_pairs<T: PartialEq>(sorted: &[T]) -> u64 {
+    sorted
+        .chunk_by(|a, b| a == b)
+        .map(|run| {
+            let len = run.len() as u64;
+            len * (len - 1) / 2
+        })
+        .sum()
+}
+
+// Merge sort returning the number of swaps (inversions) needed
+fn count_inversions<T: Copy + Ord>(list: &mut [T]) -> u64 {
+    if list.len() < 2 {
+        return 0;
+    }
+    let mid = list.len() / 2;
+    let mut inversions = count_inversions(&mut list[..mid]) + count_inversions(&mut list[mid..]);
+
+    let mut merged = Vec::with_capacity(list.len());
+    let (mut i, mut j) = (0, mid);
+    while i < mid && j < list.len() {
+        if list[j] < list[i] {
+            // Everything left in the first half is greater than list[j]
+            inversions += (mid - i) as u64;
+            merged.push(list[j]);
+            j += 1;
+        } else {
+            merged.push(list[i]);
+            i += 1;
+        }
+    }
+    merged.extend_from_slice(&list[i..mid]);
+    merged.extend_from_slice(&list[j..]);
+    list.copy_from_slice(&merged);
+    inversions
+}
+
+// Kendall's tau-b between the columns, pairing values on the same line, in
+// O(n log n) (Knight's algorithm). None if either column is constant
+pub fn kendall<T: LocationId>(list1: &[T], list2: &[T]) -> Option<f64> {
+    let mut pairs: Vec<(T, T)> = zip(list1.iter().copied(), list2.iter().copied()).collect();
+    let n = pairs.len() as u64;
+    let total_pairs = n * n.saturating_sub(1) / 2;
+
+    pairs.sort();
+    let ties_x = tied_pairs(&pairs.iter().map(|pair| pair.0).collect::<Vec<_>>());
+    let ties_xy = tied_pairs(&pairs);
+
+    // Pairs out of order by y once sorted by x are the discordant ones
+    let mut ys: Vec<T> = pairs.iter().map(|pair| pair.1).collect();
+    let discordant = count_inversions(&mut ys);
+    let ties_y = tied_pairs(&ys);
+
+    let denominator = ((total_pairs - ties_x) as f64 * (total_pairs - ties_y) as f64).sqrt();
+    let concordant_minus_discordant = total_pairs as f64 - ties_x as f64 - ties_y as f64
+        + ties_xy as f64
+        - 2.0 * discordant as f64;
+    (denominator > 0.0).then(|| concordant_minus_discordant / denominator)
+}
+
+pub struct Summary {
+    pub count: usize,
+    pub distinct: usize,
+    pub min: i128,
+    pub max: i128,
+    pub mean: f64,
+    pub median: f64,
+    pub std_dev: f64,
+}
+
+pub fn summary<T: LocationId>(list: &[T]) -> Option<Summary> {
+    let sorted: Vec<i128> = sorted(list).into_iter().map(Into::into).collect();
+    let count = sorted.len();
+    if count == 0 {
+        return None;
+    }
+
+    let mean = sorted.iter().map(|&num| num as f64).sum::<f64>() / count as f64;
+    let variance = sorted
+        .iter()
+        .map(|&num| (num as f64 - mean) * (num as f64 - mean))
+        .sum::<f64>()
+        / count as f64;
+    let median = if count % 2 == 1 {
+        sorted[count / 2] as f64
+    } else {
+        (sorted[count / 2 - 1] as f64 + sorted[count / 2] as f64) / 2.0
+    };
+
+    Some(Summary {
+        count,
+        distinct: sorted.chunk_by(|a, b| a == b).count(),
+        min: sorted[0],
+        max: sorted[count - 1],
+        mean,
+        median,
+        std_dev: variance.sqrt(),
+    })
+}
+
+fn print_summary(name: &str, summary: Option<Summary>) {
+    match summary {
+        Some(summary) => println!(
+            "{}: count {} distinct {} min {} max {} mean {:.3} median {:.1} std dev {:.3}",
+            name,
+            summary.count,
+            summary.distinct,
+            summary.min,
+            summary.max,
+            summary.mean,
+            summary.median,
+            summary.std_dev
+        ),
+        None => println!("{}: empty", name),
+    }
+}
+
+fn or_undefined(value: Option<f64>) -> String {
+    value.map_or("undefined".to_string(), |value| format!("{:.6}", value))
+}
+
+// `metrics [--metric NAME]... [FILE]`, printing every metric by default
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<(), Error> {
+    let mut selected = Vec::new();
+    let mut path = "input_1.txt".to_string();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--metric" => {
+                let metric = args.next().expect("Missing value for --metric");
+                if !METRICS.contains(&metric.as_str()) {
+                    panic!(
+                        "Unknown metric {}, expected one of {}",
+                        metric,
+                        METRICS.join(", ")
+                    );
+                }
+                selected.push(metric);
+            }
+            _ => path = arg,
+        }
+    }
+    if selected.is_empty() {
+        selected = METRICS.iter().map(|metric| metric.to_string()).collect();
+    }
+
+    let file = File::open(&path).expect("File not found");
+    let (list1, list2) = input::read_lists::<i64, _>(BufReader::new(file))?;
+
+    for metric in selected {
+        match metric.as_str() {
+            "squared-distance" => {
+                println!("squared distance: {}", squared_distance(&list1, &list2)?)
+            }
+            "max-gap" => match max_gap(&list1, &list2)? {
+                Some(gap) => println!("max gap: {}", gap),
+                None => println!("max gap: undefined"),
+            },
+            "jaccard" => println!("jaccard: {:.6}", jaccard(&list1, &list2)),
+            "overlap" => {
+                let (shared, ratio) = multiset_overlap(&list1, &list2);
+                println!("multiset overlap: {} ({:.6})", shared, ratio);
+            }
+            "spearman" => println!("spearman: {}", or_undefined(spearman(&list1, &list2))),
+            "kendall" => println!("kendall: {}", or_undefined(kendall(&list1, &list2))),
+            "summary" => {
+                print_summary("left", summary(&list1));
+                print_summary("right", summary(&list2));
+            }
+            _ => unreachable!(),
+        }
+    }
+    Ok(())
+}
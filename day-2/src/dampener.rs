@@ -0,0 +1,99 @@
+use crate::policy::{Direction, SafetyPolicy};
+
+/// The fewest levels that can be removed to make the report safe, if that is
+/// at most `max_removals` (pass the report's length for no limit). `None` also
+/// covers no subsequence being long enough for the policy.
+///
+/// O(n * max_removals) per allowed direction.
+pub fn min_removals_to_safe(
+    report: &[i32],
+    policy: &SafetyPolicy,
+    max_removals: usize,
+) -> Option<usize> {
+    let best = policy
+        .directions()
+        .iter()
+        .filter_map(|direction| min_removals_in(report, policy, *direction, max_removals))
+        .min()?;
+    // Removing the fewest levels keeps the most, so if that is too short
+    // nothing else will be long enough
+    (report.len() - best >= policy.min_len).then_some(best)
+}
+
+/// Same as `min_removals_to_safe(report, policy, 1).is_some()` in O(n)
+/// without allocating.
+pub fn is_safe_with_one_removal(report: &[i32], policy: &SafetyPolicy) -> bool {
+    policy.is_safe(report) || removal_to_safe(report, policy).is_some()
+}
+
+/// The index of a level whose removal makes an unsafe report safe, if there is
+/// one. O(n) without allocating.
+pub fn removal_to_safe(report: &[i32], policy: &SafetyPolicy) -> Option<usize> {
+    if report.len() <= policy.min_len {
+        return None;
+    }
+
+    policy.directions().iter().find_map(|&direction| {
+        // One of the first pair that breaks the rules has to go, otherwise
+        // they would still be next to each other
+        let bad = (0..report.len() - 1)
+            .find(|&i| !policy.step_ok(report[i], report[i + 1], direction))?;
+        [bad, bad + 1]
+            .into_iter()
+            .find(|&skip| is_safe_skipping(report, skip, policy, direction))
+    })
+}
+
+// Whether the report is safe in `direction` with level `skip` removed
+fn is_safe_skipping(
+    report: &[i32],
+    skip: usize,
+    policy: &SafetyPolicy,
+    direction: Direction,
+) -> bool {
+    let mut levels = report
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != skip)
+        .map(|(_, level)| *level);
+    let Some(mut previous) = levels.next() else {
+        return true;
+    };
+    levels.all(|level| {
+        let ok = policy.step_ok(previous, level, direction);
+        previous = level;
+        ok
+    })
+}
+
+fn min_removals_in(
+    report: &[i32],
+    policy: &SafetyPolicy,
+    direction: Direction,
+    max_removals: usize,
+) -> Option<usize> {
+    // removals[j] is the fewest removals from report[..=j] keeping level j
+    // that leave it safe. The previous level kept is at most max_removals + 1
+    // back, since anything further needs too many removals in between
+    let mut removals: Vec<Option<usize>> = Vec::with_capacity(report.len());
+    for j in 0..report.len() {
+        let start = j.saturating_sub(max_removals.saturating_add(1));
+        let best = (start..j)
+            .filter(|&i| policy.step_ok(report[i], report[j], direction))
+            .filter_map(|i| removals[i].map(|removed| removed + (j - i - 1)))
+            // Or drop everything before it
+            .chain([j])
+            .min()
+            .filter(|removed| *removed <= max_removals);
+        removals.push(best);
+    }
+
+    // Drop everything after the last level kept, or drop everything
+    removals
+        .iter()
+        .enumerate()
+        .filter_map(|(j, removed)| removed.map(|removed| removed + (report.len() - 1 - j)))
+        .chain([report.len()])
+        .filter(|removed| *removed <= max_removals)
+        .min()
+}
@@ -0,0 +1,183 @@
+//! Why each report is or isn't safe.
+
+use std::{
+    fmt::Write as _,
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+};
+
+use crate::{
+    dampener::removal_to_safe,
+    parse_report,
+    policy::{Direction, Rule, SafetyPolicy},
+};
+
+pub struct Violation {
+    /// Index of the first level of the offending pair
+    pub index: usize,
+    pub rule: Rule,
+}
+
+pub enum Verdict {
+    Safe,
+    /// Safe once the level at this index is removed
+    Dampened(usize),
+    Unsafe,
+}
+
+pub struct Diagnosis {
+    pub verdict: Verdict,
+    /// The allowed direction the report breaks the fewest rules in, which the
+    /// violations are relative to
+    pub direction: Option<Direction>,
+    pub violations: Vec<Violation>,
+    pub too_short: bool,
+}
+
+pub fn violations_in(
+    report: &[i32],
+    policy: &SafetyPolicy,
+    direction: Direction,
+) -> Vec<Violation> {
+    report
+        .windows(2)
+        .enumerate()
+        .filter_map(|(index, pair)| {
+            let rule = policy.check_step(pair[0], pair[1], direction).err()?;
+            Some(Violation { index, rule })
+        })
+        .collect()
+}
+
+pub fn diagnose(report: &[i32], policy: &SafetyPolicy) -> Diagnosis {
+    let (direction, violations) = policy
+        .directions()
+        .iter()
+        .map(|&direction| (Some(direction), violations_in(report, policy, direction)))
+        .min_by_key(|(_, violations)| violations.len())
+        .unwrap_or((None, Vec::new()));
+
+    let verdict = if policy.is_safe(report) {
+        Verdict::Safe
+    } else if let Some(removed) = removal_to_safe(report, policy) {
+        Verdict::Dampened(removed)
+    } else {
+        Verdict::Unsafe
+    };
+
+    Diagnosis {
+        verdict,
+        direction,
+        violations,
+        too_short: report.len() < policy.min_len,
+    }
+}
+
+fn write_text(
+    out: &mut impl Write,
+    line: usize,
+    report: &[i32],
+    diagnosis: &Diagnosis,
+) -> io::Result<()> {
+    let direction = diagnosis.direction.map_or("no direction", Direction::name);
+    match diagnosis.verdict {
+        Verdict::Safe => writeln!(out, "line {}: safe ({})", line, direction)?,
+        Verdict::Dampened(removed) => writeln!(
+            out,
+            "line {}: safe ({}) after removing level {} ({})",
+            line, direction, removed, report[removed]
+        )?,
+        Verdict::Unsafe => writeln!(out, "line {}: unsafe ({})", line, direction)?,
+    }
+    if diagnosis.too_short {
+        writeln!(out, "    only {} levels", report.len())?;
+    }
+    for violation in &diagnosis.violations {
+        writeln!(
+            out,
+            "    {} -> {} at {}: {}",
+            report[violation.index],
+            report[violation.index + 1],
+            violation.index,
+            violation.rule.name()
+        )?;
+    }
+    Ok(())
+}
+
+fn write_jsonl(
+    out: &mut impl Write,
+    line: usize,
+    report: &[i32],
+    diagnosis: &Diagnosis,
+) -> io::Result<()> {
+    let (verdict, removed) = match diagnosis.verdict {
+        Verdict::Safe => ("safe", "null".to_string()),
+        Verdict::Dampened(removed) => ("dampened", removed.to_string()),
+        Verdict::Unsafe => ("unsafe", "null".to_string()),
+    };
+    let direction = diagnosis.direction.map_or("null".to_string(), |direction| {
+        format!("\"{}\"", direction.name())
+    });
+
+    let mut violations = String::new();
+    for (i, violation) in diagnosis.violations.iter().enumerate() {
+        if i > 0 {
+            violations.push(',');
+        }
+        write!(
+            violations,
+            "{{\"index\":{},\"levels\":[{},{}],\"rule\":\"{}\"}}",
+            violation.index,
+            report[violation.index],
+            report[violation.index + 1],
+            violation.rule.name()
+        )
+        .unwrap();
+    }
+
+    writeln!(
+        out,
+        "{{\"line\":{},\"verdict\":\"{}\",\"direction\":{},\"removed\":{},\"too_short\":{},\"violations\":[{}]}}",
+        line, verdict, direction, removed, diagnosis.too_short, violations
+    )
+}
+
+// `diagnose [--format text|jsonl] [--all] [FILE]`, listing only reports that
+// aren't safe as they are unless `--all` is given
+pub fn run(policy: &SafetyPolicy, mut args: impl Iterator<Item = String>) -> io::Result<()> {
+    let mut jsonl = false;
+    let mut all = false;
+    let mut path = "input_1.txt".to_string();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                jsonl = match args.next().as_deref() {
+                    Some("text") => false,
+                    Some("jsonl") => true,
+                    _ => panic!("--format must be text or jsonl"),
+                }
+            }
+            "--all" => all = true,
+            _ => path = arg,
+        }
+    }
+
+    let file = File::open(&path).expect("File not found");
+    let reader = BufReader::new(file);
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    for (i, line) in reader.lines().enumerate() {
+        let report = parse_report(&line.unwrap());
+        let diagnosis = diagnose(&report, policy);
+        if !all && matches!(diagnosis.verdict, Verdict::Safe) {
+            continue;
+        }
+        let written = if jsonl {
+            write_jsonl(&mut out, i + 1, &report, &diagnosis)
+        } else {
+            write_text(&mut out, i + 1, &report, &diagnosis)
+        };
+        written?;
+    }
+    out.flush()
+}
@@ -0,0 +1,182 @@
+//! The biggest safe parts of reports, and ranking reports by how much of them
+//! would have to go to make them safe.
+
+use std::{
+    cmp::Ordering,
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    ops::Range,
+};
+
+use crate::{
+    parse_report,
+    policy::{Direction, SafetyPolicy},
+};
+
+/// Indices of the longest subsequence of levels that is safe, or `None` if no
+/// subsequence is long enough for the policy. O(n^2) per allowed direction.
+pub fn longest_safe_subsequence(report: &[i32], policy: &SafetyPolicy) -> Option<Vec<usize>> {
+    policy
+        .directions()
+        .iter()
+        .map(|&direction| longest_subsequence_in(report, policy, direction))
+        .max_by_key(Vec::len)
+        .filter(|indices| indices.len() >= policy.min_len)
+}
+
+fn longest_subsequence_in(
+    report: &[i32],
+    policy: &SafetyPolicy,
+    direction: Direction,
+) -> Vec<usize> {
+    // longest[j] is the length of the longest safe subsequence ending at level
+    // j, with the level before it
+    let mut longest: Vec<(usize, Option<usize>)> = Vec::with_capacity(report.len());
+    for j in 0..report.len() {
+        let best = (0..j)
+            .filter(|&i| policy.step_ok(report[i], report[j], direction))
+            .map(|i| (longest[i].0 + 1, Some(i)))
+            .max_by_key(|(len, _)| *len)
+            .unwrap_or((1, None));
+        longest.push(best);
+    }
+
+    let Some(mut last) = (0..report.len()).max_by_key(|&j| longest[j].0) else {
+        return Vec::new();
+    };
+    let mut indices = vec![last];
+    while let Some(previous) = longest[last].1 {
+        indices.push(previous);
+        last = previous;
+    }
+    indices.reverse();
+    indices
+}
+
+/// The longest contiguous run of levels that is safe, or `None` if none is
+/// long enough for the policy. O(n) per allowed direction.
+pub fn longest_safe_run(report: &[i32], policy: &SafetyPolicy) -> Option<Range<usize>> {
+    policy
+        .directions()
+        .iter()
+        .map(|&direction| longest_run_in(report, policy, direction))
+        .max_by_key(Range::len)
+        .filter(|run| run.len() >= policy.min_len)
+}
+
+fn longest_run_in(report: &[i32], policy: &SafetyPolicy, direction: Direction) -> Range<usize> {
+    let mut longest = 0..report.len().min(1);
+    let mut start = 0;
+    for j in 1..report.len() {
+        if !policy.step_ok(report[j - 1], report[j], direction) {
+            start = j;
+        }
+        if j + 1 - start > longest.len() {
+            longest = start..j + 1;
+        }
+    }
+    longest
+}
+
+struct Ranked {
+    line: usize,
+    len: usize,
+    /// Levels that have to be removed to make the report safe, if it can be
+    removals: Option<usize>,
+    run: Option<Range<usize>>,
+}
+
+impl Ranked {
+    // Greater is further from safe: can't be made safe at all, then more
+    // removals, then more of the report removed. The share removed is compared
+    // by cross-multiplying, so an empty report just ties
+    fn cmp_distance(&self, other: &Ranked) -> Ordering {
+        match (self.removals, other.removals) {
+            (Some(a), Some(b)) => a.cmp(&b).then_with(|| (a * other.len).cmp(&(b * self.len))),
+            (a, b) => a.is_none().cmp(&b.is_none()),
+        }
+    }
+}
+
+fn or_dash<T>(value: Option<T>, show: impl FnOnce(T) -> String) -> String {
+    value.map_or("-".to_string(), show)
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> (usize, String) {
+    let mut top = usize::MAX;
+    let mut path = "input_1.txt".to_string();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--top" => {
+                top = args
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .expect("--top needs a number")
+            }
+            _ => path = arg,
+        }
+    }
+    (top, path)
+}
+
+// `longest [FILE]`, listing the longest safe subsequence and run of each report
+pub fn run_longest(policy: &SafetyPolicy, args: impl Iterator<Item = String>) -> io::Result<()> {
+    let (_, path) = parse_args(args);
+    let file = File::open(&path).expect("File not found");
+    let reader = BufReader::new(file);
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    for (i, line) in reader.lines().enumerate() {
+        let report = parse_report(&line.unwrap());
+        let subsequence = longest_safe_subsequence(&report, policy);
+        let run = longest_safe_run(&report, policy);
+        writeln!(
+            out,
+            "line {}: subsequence {} run {}",
+            i + 1,
+            or_dash(subsequence, |indices| format!("{:?}", indices)),
+            or_dash(run, |run| format!("{:?}", run))
+        )?;
+    }
+    out.flush()
+}
+
+// `rank [--top N] [FILE]`, listing reports furthest from safe first
+pub fn run_rank(policy: &SafetyPolicy, args: impl Iterator<Item = String>) -> io::Result<()> {
+    let (top, path) = parse_args(args);
+    let file = File::open(&path).expect("File not found");
+    let reader = BufReader::new(file);
+    let mut ranked: Vec<Ranked> = reader
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let report = parse_report(&line.unwrap());
+            Ranked {
+                line: i + 1,
+                len: report.len(),
+                removals: longest_safe_subsequence(&report, policy)
+                    .map(|subsequence| report.len() - subsequence.len()),
+                run: longest_safe_run(&report, policy),
+            }
+        })
+        .collect();
+    // Stable, so equally far reports stay in file order
+    ranked.sort_by(|a, b| b.cmp_distance(a));
+
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    writeln!(
+        out,
+        "{:>6} {:>6} {:>9} {:>12}",
+        "line", "levels", "removals", "longest run"
+    )?;
+    for ranked in ranked.iter().take(top) {
+        writeln!(
+            out,
+            "{:>6} {:>6} {:>9} {:>12}",
+            ranked.line,
+            ranked.len,
+            or_dash(ranked.removals, |removals| removals.to_string()),
+            or_dash(ranked.run.clone(), |run| format!("{:?}", run))
+        )?;
+    }
+    out.flush()
+}
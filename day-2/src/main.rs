@@ -1,30 +1,119 @@
+mod dampener;
+mod diagnose;
+mod longest;
+mod policy;
+mod repair;
+mod stats;
+mod stream;
+mod verify;
+
+use dampener::{is_safe_with_one_removal, min_removals_to_safe};
+use policy::SafetyPolicy;
 use std::{
+    env, fmt,
     fs::File,
-    io::{BufRead, BufReader},
+    io::{self, BufReader},
+    num::ParseIntError,
+    process,
 };
+use stream::Parallelism;
 
 fn main() {
-    println!("{}", part_1());
-    println!("{}", part_2());
-}
+    let mut args = env::args().skip(1).peekable();
+    let command = args.next_if(|arg| !arg.starts_with("--"));
+
+    // Policy flags apply to every command, anything else is the command's own
+    let mut policy = SafetyPolicy::default();
+    let mut max_removals = 1;
+    let mut parallelism = Parallelism::default();
+    let mut command_args = Vec::new();
+    while let Some(arg) = args.next() {
+        match policy.take_flag(&arg, &mut args) {
+            Ok(true) => {}
+            Ok(false) if arg == "--max-removals" => {
+                max_removals = args
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .expect("--max-removals needs a number");
+            }
+            Ok(false) => match parallelism.take_flag(&arg, &mut args) {
+                Ok(true) => {}
+                Ok(false) => command_args.push(arg),
+                Err(err) => flag_error(err),
+            },
+            Err(err) => flag_error(err),
+        }
+    }
 
-fn report_is_safe(report: &[i32]) -> bool {
-    let monotonic = report.iter().is_sorted() || report.iter().rev().is_sorted();
-    let differ_by_1_to_3 = report
-        .windows(2)
-        .map(|nums| match nums {
-            &[num1, num2] => {
-                let diff = (num2 - num1).abs();
-                (1..=3).contains(&diff)
+    match command.as_deref() {
+        // `count FILE` is both parts on the same file
+        None | Some("count") if command_args.len() <= usize::from(command.is_some()) => {
+            let (path_1, path_2) = match command_args.first() {
+                Some(path) => (path.as_str(), path.as_str()),
+                None => ("input_1.txt", "input_2.txt"),
+            };
+            let answers = part_1(&policy, path_1, parallelism).and_then(|part_1| {
+                println!("{}", part_1);
+                part_2(&policy, path_2, max_removals, parallelism)
+            });
+            match answers {
+                Ok(part_2) => println!("{}", part_2),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    process::exit(1);
+                }
             }
-            _ => panic!("Invalid input"),
-        })
-        .all(|cond| cond);
-    monotonic && differ_by_1_to_3
+        }
+        Some("generate") => finish(stream::generate(command_args.into_iter())),
+        Some("verify") => verify::run(&policy, command_args.into_iter()),
+        Some("diagnose") => finish(diagnose::run(&policy, command_args.into_iter())),
+        Some("stats") => finish(stats::run(&policy, command_args.into_iter())),
+        Some("repair") => finish(repair::run(&policy, command_args.into_iter())),
+        Some("longest") => finish(longest::run_longest(&policy, command_args.into_iter())),
+        Some("rank") => finish(longest::run_rank(&policy, command_args.into_iter())),
+        _ => usage(),
+    }
+}
+
+// Output cut short by whatever it was piped into closing, like `head`, is just
+// an early exit
+fn finish(written: io::Result<()>) {
+    match written {
+        Err(err) if err.kind() != io::ErrorKind::BrokenPipe => {
+            eprintln!("Failed to write to stdout: {}", err);
+            process::exit(1);
+        }
+        _ => {}
+    }
+}
+
+fn flag_error(err: impl fmt::Display) -> ! {
+    eprintln!("{}", err);
+    process::exit(2);
 }
 
-fn report_is_1_away_from_safe(report: &[i32]) -> bool {
-    if report_is_safe(report) {
+fn usage() -> ! {
+    eprintln!(
+        "Usage: day-2 [verify [COUNT] [SEED] | diagnose [--format text|jsonl] [--all] [FILE] \
+         | stats [--format text|csv] [--max-budget K] [FILE] \
+         | repair [--allow-removals] [--dataset] [FILE] | longest [FILE] | rank [--top N] [FILE] \
+         | generate REPORTS [SEED] | count [FILE]] [--max-removals K] [--threads N] [--chunk-lines N] {}",
+        policy::USAGE
+    );
+    process::exit(2);
+}
+
+fn try_parse_report(line: &str) -> Result<Vec<i32>, ParseIntError> {
+    line.split_whitespace().map(|num| num.parse()).collect()
+}
+
+fn parse_report(line: &str) -> Vec<i32> {
+    try_parse_report(line).unwrap()
+}
+
+// Brute force version of `is_safe_with_one_removal`, kept to check it against
+fn report_is_1_away_from_safe(report: &[i32], policy: &SafetyPolicy) -> bool {
+    if policy.is_safe(report) {
         return true;
     }
 
@@ -38,45 +127,38 @@ fn report_is_1_away_from_safe(report: &[i32]) -> bool {
                 false => None,
             })
             .collect::<Vec<i32>>();
-        if report_is_safe(&modified_report) {
+        if policy.is_safe(&modified_report) {
             return true;
         }
     }
     false
 }
 
-fn part_1() -> usize {
-    let file = File::open("input_1.txt").expect("File not found");
-    let reader = BufReader::new(file);
-
-    reader
-        .lines()
-        .map(|line| {
-            let report: Vec<i32> = line
-                .unwrap()
-                .split_whitespace()
-                .map(|num| num.parse().unwrap())
-                .collect();
-            report_is_safe(&report)
-        })
-        .filter(|cond| *cond)
-        .count()
+fn part_1(
+    policy: &SafetyPolicy,
+    path: &str,
+    parallelism: Parallelism,
+) -> Result<usize, stream::Error> {
+    let file = File::open(path).expect("File not found");
+    stream::count_reports(BufReader::new(file), parallelism, |report| {
+        policy.is_safe(report)
+    })
 }
 
-fn part_2() -> usize {
-    let file = File::open("input_2.txt").expect("File not found");
-    let reader = BufReader::new(file);
-
-    reader
-        .lines()
-        .map(|line| {
-            let report: Vec<i32> = line
-                .unwrap()
-                .split_whitespace()
-                .map(|num| num.parse().unwrap())
-                .collect();
-            report_is_1_away_from_safe(&report)
-        })
-        .filter(|cond| *cond)
-        .count()
+// Reports that are safe after removing at most `max_removals` levels
+fn part_2(
+    policy: &SafetyPolicy,
+    path: &str,
+    max_removals: usize,
+    parallelism: Parallelism,
+) -> Result<usize, stream::Error> {
+    let file = File::open(path).expect("File not found");
+    stream::count_reports(
+        BufReader::new(file),
+        parallelism,
+        |report| match max_removals {
+            1 => is_safe_with_one_removal(report, policy),
+            _ => min_removals_to_safe(report, policy, max_removals).is_some(),
+        },
+    )
 }
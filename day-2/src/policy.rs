@@ -0,0 +1,201 @@
+use std::{fmt, fs, ops::RangeInclusive};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Direction {
+    Increasing,
+    Decreasing,
+}
+
+impl Direction {
+    pub fn name(self) -> &'static str {
+        match self {
+            Direction::Increasing => "increasing",
+            Direction::Decreasing => "decreasing",
+        }
+    }
+}
+
+/// Rules a pair of adjacent levels can break
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rule {
+    DirectionChange,
+    ZeroStep,
+    StepTooSmall,
+    StepTooLarge,
+}
+
+impl Rule {
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::DirectionChange => "direction_change",
+            Rule::ZeroStep => "zero_step",
+            Rule::StepTooSmall => "step_too_small",
+            Rule::StepTooLarge => "step_too_large",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DirectionConstraint {
+    Any,
+    Only(Direction),
+}
+
+/// What makes a report safe. The default is the puzzle's: strictly increasing
+/// or decreasing, with adjacent levels differing by 1 to 3.
+#[derive(Clone)]
+pub struct SafetyPolicy {
+    /// Allowed size of the step between adjacent levels
+    pub steps: RangeInclusive<i64>,
+    /// Whether a zero step breaks monotonicity. Non-strict policies still need
+    /// 0 in `steps` to allow equal adjacent levels
+    pub strict: bool,
+    pub direction: DirectionConstraint,
+    /// Reports with fewer levels are unsafe
+    pub min_len: usize,
+}
+
+impl Default for SafetyPolicy {
+    fn default() -> Self {
+        SafetyPolicy {
+            steps: 1..=3,
+            strict: true,
+            direction: DirectionConstraint::Any,
+            min_len: 0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PolicyError(String);
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+pub const USAGE: &str = "[--min-step N] [--max-step N] [--non-strict] \
+                         [--direction any|increasing|decreasing] [--min-len N] [--config FILE]";
+
+impl SafetyPolicy {
+    /// Directions a safe report may go in
+    pub fn directions(&self) -> &'static [Direction] {
+        match self.direction {
+            DirectionConstraint::Any => &[Direction::Increasing, Direction::Decreasing],
+            DirectionConstraint::Only(Direction::Increasing) => &[Direction::Increasing],
+            DirectionConstraint::Only(Direction::Decreasing) => &[Direction::Decreasing],
+        }
+    }
+
+    /// The rule broken going from level `a` to `b` in a report going in `direction`
+    pub fn check_step(&self, a: i32, b: i32, direction: Direction) -> Result<(), Rule> {
+        let step = match direction {
+            Direction::Increasing => b as i64 - a as i64,
+            Direction::Decreasing => a as i64 - b as i64,
+        };
+        if step < 0 {
+            Err(Rule::DirectionChange)
+        } else if step == 0 && (self.strict || !self.steps.contains(&0)) {
+            Err(Rule::ZeroStep)
+        } else if step < *self.steps.start() {
+            Err(Rule::StepTooSmall)
+        } else if step > *self.steps.end() {
+            Err(Rule::StepTooLarge)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Whether going from level `a` to `b` is allowed in a report going in `direction`
+    pub fn step_ok(&self, a: i32, b: i32, direction: Direction) -> bool {
+        self.check_step(a, b, direction).is_ok()
+    }
+
+    pub fn is_safe_in(&self, report: &[i32], direction: Direction) -> bool {
+        report
+            .windows(2)
+            .all(|pair| self.step_ok(pair[0], pair[1], direction))
+    }
+
+    pub fn is_safe(&self, report: &[i32]) -> bool {
+        report.len() >= self.min_len
+            && self
+                .directions()
+                .iter()
+                .any(|direction| self.is_safe_in(report, *direction))
+    }
+
+    /// Applies `flag` if it is a policy flag, taking its value from `args`.
+    /// Returns whether it was one
+    pub fn take_flag(
+        &mut self,
+        flag: &str,
+        args: &mut impl Iterator<Item = String>,
+    ) -> Result<bool, PolicyError> {
+        let key = match flag {
+            "--non-strict" => {
+                self.strict = false;
+                return Ok(true);
+            }
+            "--config" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| PolicyError("Missing value for --config".to_string()))?;
+                self.apply_config(&path)?;
+                return Ok(true);
+            }
+            "--min-step" => "min_step",
+            "--max-step" => "max_step",
+            "--direction" => "direction",
+            "--min-len" => "min_len",
+            _ => return Ok(false),
+        };
+        let value = args
+            .next()
+            .ok_or_else(|| PolicyError(format!("Missing value for {}", flag)))?;
+        self.set(key, &value)?;
+        Ok(true)
+    }
+
+    /// Reads `key = value` lines with the same keys as the flags (`min_step`,
+    /// `max_step`, `strict`, `direction`, `min_len`). `#` starts a comment
+    pub fn apply_config(&mut self, path: &str) -> Result<(), PolicyError> {
+        let config = fs::read_to_string(path)
+            .map_err(|err| PolicyError(format!("Can't read {}: {}", path, err)))?;
+        for (i, line) in config.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| PolicyError(format!("{}:{}: expected key = value", path, i + 1)))?;
+            self.set(key.trim(), value.trim())
+                .map_err(|err| PolicyError(format!("{}:{}: {}", path, i + 1, err)))?;
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), PolicyError> {
+        let invalid = || PolicyError(format!("Invalid value for {}: {}", key, value));
+        match key {
+            "min_step" => self.steps = value.parse().map_err(|_| invalid())?..=*self.steps.end(),
+            "max_step" => {
+                self.steps = *self.steps.start()..=value.parse().map_err(|_| invalid())?
+            }
+            "strict" => self.strict = value.parse().map_err(|_| invalid())?,
+            "direction" => {
+                self.direction = match value {
+                    "any" => DirectionConstraint::Any,
+                    "increasing" => DirectionConstraint::Only(Direction::Increasing),
+                    "decreasing" => DirectionConstraint::Only(Direction::Decreasing),
+                    _ => return Err(invalid()),
+                }
+            }
+            "min_len" => self.min_len = value.parse().map_err(|_| invalid())?,
+            _ => return Err(PolicyError(format!("Unknown policy setting {}", key))),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,231 @@
+//! Making unsafe reports safe by changing levels' values, optionally mixed with
+//! removing levels, for producing a corrected copy of the data.
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+};
+
+use crate::{
+    parse_report,
+    policy::{Direction, SafetyPolicy},
+};
+
+pub struct Repair {
+    /// The safe report
+    pub levels: Vec<i32>,
+    /// Indices into the original report
+    pub changed: Vec<usize>,
+    pub removed: Vec<usize>,
+}
+
+impl Repair {
+    pub fn cost(&self) -> usize {
+        self.changed.len() + self.removed.len()
+    }
+}
+
+/// The repair with the fewest edits, then the fewest removals, or `None` if
+/// there is none (the report is shorter than the policy's `min_len`, or the
+/// repaired levels wouldn't fit in an `i32`). Removals are only used if
+/// `allow_removals` is set.
+///
+/// Levels kept as they are must be far enough apart for the changed levels
+/// between them to step between them, and every level not kept costs one edit
+/// whether it is changed or removed, so this maximises the levels kept. With
+/// removals allowed, the ones between two kept levels are only needed when
+/// there are too many levels between them for the steps to be small enough.
+/// O(n^3) per allowed direction.
+pub fn repair(report: &[i32], policy: &SafetyPolicy, allow_removals: bool) -> Option<Repair> {
+    if report.len() < policy.min_len {
+        return None;
+    }
+    let max_removals = match allow_removals {
+        true => report.len() - policy.min_len,
+        false => 0,
+    };
+    policy
+        .directions()
+        .iter()
+        .filter_map(|&direction| repair_in(report, policy, direction, max_removals))
+        .min_by_key(|repair| (repair.cost(), repair.removed.len()))
+}
+
+// Smallest and largest step allowed going in the report's direction
+fn step_bounds(policy: &SafetyPolicy) -> (i64, i64) {
+    let smallest = match policy.strict {
+        true => (*policy.steps.start()).max(1),
+        false => (*policy.steps.start()).max(0),
+    };
+    (smallest, *policy.steps.end())
+}
+
+fn repair_in(
+    report: &[i32],
+    policy: &SafetyPolicy,
+    direction: Direction,
+    max_removals: usize,
+) -> Option<Repair> {
+    if report.is_empty() {
+        return Some(Repair {
+            levels: Vec::new(),
+            changed: Vec::new(),
+            removed: Vec::new(),
+        });
+    }
+    let (smallest, largest) = step_bounds(policy);
+    if smallest > largest {
+        // Only a single level is safe, so keep one and remove the rest
+        return (report.len() - 1 <= max_removals).then(|| Repair {
+            levels: vec![report[0]],
+            changed: Vec::new(),
+            removed: (1..report.len()).collect(),
+        });
+    }
+
+    // Levels as if the report were increasing
+    let sign = match direction {
+        Direction::Increasing => 1,
+        Direction::Decreasing => -1,
+    };
+    let levels: Vec<i64> = report.iter().map(|&level| sign * level as i64).collect();
+
+    // The most of the levels strictly between kept levels `i` and `j` that can
+    // be changed rather than removed, if any number of them works
+    let max_changed = |i: usize, j: usize| {
+        let between = j - i - 1;
+        let distance = levels[j] - levels[i];
+        let fits = |changed: usize| {
+            let steps = changed as i64 + 1;
+            steps * smallest <= distance && distance <= steps * largest
+        };
+        match max_removals {
+            0 => fits(between).then_some(between),
+            _ => (0..=between).rev().find(|&changed| fits(changed)),
+        }
+    };
+
+    // removals[j][k] is the fewest removals needed keeping k + 1 levels of
+    // report[..=j] including level j, with the previous level kept
+    let n = report.len();
+    let mut removals: Vec<Vec<Option<(usize, usize)>>> = vec![vec![None; n]; n];
+    for j in 0..n {
+        removals[j][0] = Some((0, j));
+        for i in 0..j {
+            let Some(changed) = max_changed(i, j) else {
+                continue;
+            };
+            let removed = j - i - 1 - changed;
+            for k in 1..=i + 1 {
+                let Some((before, _)) = removals[i][k - 1] else {
+                    continue;
+                };
+                let total = before + removed;
+                if total <= max_removals && removals[j][k].is_none_or(|(best, _)| total < best) {
+                    removals[j][k] = Some((total, i));
+                }
+            }
+        }
+    }
+
+    // Keep as many as possible
+    let (mut j, mut k) = (0..n)
+        .flat_map(|j| (0..=j).map(move |k| (j, k)))
+        .filter(|&(j, k)| removals[j][k].is_some())
+        .max_by_key(|&(j, k)| (k, std::cmp::Reverse(removals[j][k].unwrap().0)))?;
+    let mut kept = vec![j];
+    while k > 0 {
+        j = removals[j][k].unwrap().1;
+        k -= 1;
+        kept.push(j);
+    }
+    kept.reverse();
+
+    // Levels before the first kept one and after the last one are changed to
+    // continue with the smallest step
+    let mut repaired: Vec<(usize, i64)> = Vec::with_capacity(n);
+    let (first, last) = (kept[0], kept[kept.len() - 1]);
+    repaired.extend((0..first).map(|i| (i, levels[first] - (first - i) as i64 * smallest)));
+    repaired.push((first, levels[first]));
+    for pair in kept.windows(2) {
+        let (i, j) = (pair[0], pair[1]);
+        // Change the first levels between them and remove the rest, spreading
+        // the distance as evenly as possible over the steps
+        let changed = max_changed(i, j).unwrap();
+        let steps = changed as i64 + 1;
+        let distance = levels[j] - levels[i];
+        let mut level = levels[i];
+        for step in 0..changed as i64 {
+            level += distance / steps + i64::from(step < distance % steps);
+            repaired.push((i + 1 + step as usize, level));
+        }
+        repaired.push((j, levels[j]));
+    }
+    repaired.extend((last + 1..n).map(|i| (i, levels[last] + (i - last) as i64 * smallest)));
+
+    let mut result = Repair {
+        levels: Vec::with_capacity(repaired.len()),
+        changed: Vec::new(),
+        removed: Vec::new(),
+    };
+    let mut next = 0;
+    for (i, level) in repaired {
+        result.removed.extend(next..i);
+        next = i + 1;
+        let level = i32::try_from(sign * level).ok()?;
+        if level != report[i] {
+            result.changed.push(i);
+        }
+        result.levels.push(level);
+    }
+    Some(result)
+}
+
+// `repair [--allow-removals] [--dataset] [FILE]`, listing the repair for each
+// unsafe report, or printing every report repaired in the input's format with
+// `--dataset`
+pub fn run(policy: &SafetyPolicy, args: impl Iterator<Item = String>) -> io::Result<()> {
+    let mut allow_removals = false;
+    let mut dataset = false;
+    let mut path = "input_1.txt".to_string();
+    for arg in args {
+        match arg.as_str() {
+            "--allow-removals" => allow_removals = true,
+            "--dataset" => dataset = true,
+            _ => path = arg,
+        }
+    }
+
+    let file = File::open(&path).expect("File not found");
+    let reader = BufReader::new(file);
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    for (i, line) in reader.lines().enumerate() {
+        let report = parse_report(&line.unwrap());
+        let repaired = repair(&report, policy, allow_removals);
+        let written = match (dataset, repaired) {
+            (true, Some(repaired)) => writeln!(out, "{}", join(&repaired.levels)),
+            (true, None) => panic!("Line {} can't be repaired", i + 1),
+            (false, Some(repaired)) if repaired.cost() == 0 => Ok(()),
+            (false, Some(repaired)) => writeln!(
+                out,
+                "line {}: {} -> {} (changed {:?}, removed {:?})",
+                i + 1,
+                join(&report),
+                join(&repaired.levels),
+                repaired.changed,
+                repaired.removed
+            ),
+            (false, None) => writeln!(out, "line {}: can't be repaired", i + 1),
+        };
+        written?;
+    }
+    out.flush()
+}
+
+fn join(levels: &[i32]) -> String {
+    levels
+        .iter()
+        .map(|level| level.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,201 @@
+//! Summary of a whole file of reports, for checking the data over.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+};
+
+use crate::{
+    dampener::min_removals_to_safe,
+    diagnose::diagnose,
+    parse_report,
+    policy::{Rule, SafetyPolicy},
+};
+
+#[derive(Default)]
+pub struct Stats {
+    pub reports: usize,
+    pub safe: usize,
+    /// Unsafe reports breaking each rule at least once, in the direction
+    /// they come closest to being safe in
+    pub by_rule: BTreeMap<&'static str, usize>,
+    pub lengths: BTreeMap<usize, usize>,
+    /// Differences between adjacent levels, signed
+    pub steps: BTreeMap<i64, usize>,
+    /// Reports needing exactly this many removals to be safe, with `None` for
+    /// ones no number of removals fixes
+    pub removals: BTreeMap<Option<usize>, usize>,
+    /// Index of the first level of each pair breaking a rule, across unsafe
+    /// reports
+    pub violation_positions: BTreeMap<usize, usize>,
+}
+
+impl Stats {
+    pub fn add(&mut self, report: &[i32], policy: &SafetyPolicy) {
+        self.reports += 1;
+        *self.lengths.entry(report.len()).or_insert(0) += 1;
+        for pair in report.windows(2) {
+            *self
+                .steps
+                .entry(pair[1] as i64 - pair[0] as i64)
+                .or_insert(0) += 1;
+        }
+        let removals = min_removals_to_safe(report, policy, report.len());
+        *self.removals.entry(removals).or_insert(0) += 1;
+
+        if policy.is_safe(report) {
+            self.safe += 1;
+            return;
+        }
+        let diagnosis = diagnose(report, policy);
+        if diagnosis.too_short {
+            *self.by_rule.entry("too_short").or_insert(0) += 1;
+        }
+        let mut rules: Vec<Rule> = diagnosis.violations.iter().map(|v| v.rule).collect();
+        rules.sort_by_key(|rule| rule.name());
+        rules.dedup();
+        for rule in rules {
+            *self.by_rule.entry(rule.name()).or_insert(0) += 1;
+        }
+        for violation in &diagnosis.violations {
+            *self.violation_positions.entry(violation.index).or_insert(0) += 1;
+        }
+    }
+
+    /// Reports safe after removing at most `budget` levels
+    pub fn safe_within(&self, budget: usize) -> usize {
+        self.removals
+            .iter()
+            .filter(|(removals, _)| removals.is_some_and(|removals| removals <= budget))
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    // (section, key, count) for every row, in the order they are printed
+    fn rows(&self, max_budget: usize) -> Vec<(&'static str, String, usize)> {
+        let mut rows = vec![
+            ("reports", "total".to_string(), self.reports),
+            ("reports", "safe".to_string(), self.safe),
+            ("reports", "unsafe".to_string(), self.reports - self.safe),
+        ];
+        rows.extend(
+            self.by_rule
+                .iter()
+                .map(|(rule, count)| ("failure_reason", rule.to_string(), *count)),
+        );
+        rows.extend(
+            self.lengths
+                .iter()
+                .map(|(len, count)| ("length", len.to_string(), *count)),
+        );
+        rows.extend(
+            self.steps
+                .iter()
+                .map(|(step, count)| ("step", step.to_string(), *count)),
+        );
+        rows.extend((0..=max_budget).map(|budget| {
+            (
+                "safe_within_removals",
+                budget.to_string(),
+                self.safe_within(budget),
+            )
+        }));
+        rows.push((
+            "safe_within_removals",
+            "never".to_string(),
+            self.removals.get(&None).copied().unwrap_or(0),
+        ));
+        rows.extend(
+            self.violation_positions
+                .iter()
+                .map(|(index, count)| ("violation_position", index.to_string(), *count)),
+        );
+        rows
+    }
+
+    pub fn write_text(&self, out: &mut impl Write, max_budget: usize) -> io::Result<()> {
+        let rows = self.rows(max_budget);
+        let width = rows.iter().map(|(_, key, _)| key.len()).max().unwrap_or(0);
+        // Bars are scaled per section so small sections are still readable
+        let mut section = "";
+        let mut section_max = 0;
+        for (i, (name, key, count)) in rows.iter().enumerate() {
+            if *name != section {
+                section = name;
+                section_max = rows[i..]
+                    .iter()
+                    .take_while(|(other, _, _)| other == name)
+                    .map(|(_, _, count)| *count)
+                    .max()
+                    .unwrap_or(0);
+                if i > 0 {
+                    writeln!(out)?;
+                }
+                writeln!(out, "{}", name)?;
+            }
+            let bar = match section_max {
+                0 => 0,
+                max => (count * 40).div_ceil(max),
+            };
+            writeln!(
+                out,
+                "  {:>width$} {:>8} {}",
+                key,
+                count,
+                "#".repeat(bar),
+                width = width
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn write_csv(&self, out: &mut impl Write, max_budget: usize) -> io::Result<()> {
+        writeln!(out, "section,key,count")?;
+        for (section, key, count) in self.rows(max_budget) {
+            writeln!(out, "{},{},{}", section, key, count)?;
+        }
+        Ok(())
+    }
+}
+
+// `stats [--format text|csv] [--max-budget K] [FILE]`, with reports safe
+// within 0 to K removals (3 by default)
+pub fn run(policy: &SafetyPolicy, mut args: impl Iterator<Item = String>) -> io::Result<()> {
+    let mut csv = false;
+    let mut max_budget = 3;
+    let mut path = "input_1.txt".to_string();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                csv = match args.next().as_deref() {
+                    Some("text") => false,
+                    Some("csv") => true,
+                    _ => panic!("--format must be text or csv"),
+                }
+            }
+            "--max-budget" => {
+                max_budget = args
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .expect("--max-budget needs a number")
+            }
+            _ => path = arg,
+        }
+    }
+
+    let file = File::open(&path).expect("File not found");
+    let reader = BufReader::new(file);
+    let mut stats = Stats::default();
+    for line in reader.lines() {
+        stats.add(&parse_report(&line.unwrap()), policy);
+    }
+
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    let written = if csv {
+        stats.write_csv(&mut out, max_budget)
+    } else {
+        stats.write_text(&mut out, max_budget)
+    };
+    written.and_then(|_| out.flush())
+}
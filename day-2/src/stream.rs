@@ -0,0 +1,214 @@
+//! Evaluating big files of reports across threads: the file is read in chunks
+//! of lines that workers pick up, with only a few chunks in flight at once.
+
+use std::{
+    fmt,
+    io::{self, BufRead, BufWriter, Write},
+    num::{NonZeroUsize, ParseIntError},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Mutex,
+    },
+    thread,
+};
+
+use rng::Rng;
+
+use crate::{try_parse_report, verify::random_report};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// A level that isn't a number, on a 1-based line
+    Parse(usize, ParseIntError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{}", err),
+            Error::Parse(line, err) => write!(f, "Invalid report on line {}: {}", line, err),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParallelismError(String);
+
+impl fmt::Display for ParallelismError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Parallelism {
+    pub threads: usize,
+    pub chunk_lines: usize,
+}
+
+impl Default for Parallelism {
+    fn default() -> Self {
+        Parallelism {
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            chunk_lines: 4096,
+        }
+    }
+}
+
+impl Parallelism {
+    /// Sets `--threads` or `--chunk-lines` from the value in `args`. Returns
+    /// whether `flag` was one of them, failing if its value isn't positive
+    pub fn take_flag(
+        &mut self,
+        flag: &str,
+        args: &mut impl Iterator<Item = String>,
+    ) -> Result<bool, ParallelismError> {
+        let setting = match flag {
+            "--threads" => &mut self.threads,
+            "--chunk-lines" => &mut self.chunk_lines,
+            _ => return Ok(false),
+        };
+        *setting = args
+            .next()
+            .and_then(|value| value.parse().ok())
+            .filter(|value| *value > 0)
+            .ok_or_else(|| ParallelismError(format!("{} needs a positive number", flag)))?;
+        Ok(true)
+    }
+}
+
+// Lines starting at a 1-based line number
+struct Chunk {
+    first_line: usize,
+    lines: Vec<String>,
+}
+
+/// Number of reports in `reader` that `is_counted` is true for. On a parse
+/// error, the one on the earliest line is returned
+pub fn count_reports(
+    reader: impl BufRead,
+    parallelism: Parallelism,
+    is_counted: impl Fn(&[i32]) -> bool + Sync,
+) -> Result<usize, Error> {
+    // Bounded, so reading blocks rather than getting ahead of the workers
+    let (sender, receiver) = mpsc::sync_channel::<Chunk>(parallelism.threads * 2);
+    let receiver = Mutex::new(receiver);
+    let failed = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..parallelism.threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut count = 0;
+                    let mut first_error = None;
+                    // Chunks keep being taken after an error so the reader
+                    // never blocks on a full channel
+                    loop {
+                        // Only holding the lock while waiting for a chunk
+                        let Ok(chunk) = receiver.lock().unwrap().recv() else {
+                            break;
+                        };
+                        let counted = count_chunk(&chunk, &is_counted).inspect_err(|_| {
+                            failed.store(true, Ordering::Relaxed);
+                        });
+                        match counted {
+                            Ok(chunk_count) => count += chunk_count,
+                            Err(err) => first_error = first_error.or(Some(err)),
+                        }
+                    }
+                    first_error.map_or(Ok(count), Err)
+                })
+            })
+            .collect();
+
+        let read = read_chunks(reader, parallelism.chunk_lines, &failed, |chunk| {
+            // Workers only stop once the sender is dropped
+            sender.send(chunk).unwrap();
+        });
+        drop(sender);
+
+        // Every chunk read gets counted, so the earliest error is the one on
+        // the lowest line of any worker's first
+        let mut total = 0;
+        let mut first_error: Option<(usize, ParseIntError)> = None;
+        for worker in workers {
+            match worker.join().unwrap() {
+                Ok(count) => total += count,
+                Err((line, err)) => {
+                    if first_error.as_ref().is_none_or(|(first, _)| line < *first) {
+                        first_error = Some((line, err));
+                    }
+                }
+            }
+        }
+        read?;
+        match first_error {
+            Some((line, err)) => Err(Error::Parse(line, err)),
+            None => Ok(total),
+        }
+    })
+}
+
+fn count_chunk(
+    chunk: &Chunk,
+    is_counted: impl Fn(&[i32]) -> bool,
+) -> Result<usize, (usize, ParseIntError)> {
+    let mut count = 0;
+    for (i, line) in chunk.lines.iter().enumerate() {
+        let report = try_parse_report(line).map_err(|err| (chunk.first_line + i, err))?;
+        count += usize::from(is_counted(&report));
+    }
+    Ok(count)
+}
+
+fn read_chunks(
+    reader: impl BufRead,
+    chunk_lines: usize,
+    failed: &AtomicBool,
+    mut send: impl FnMut(Chunk),
+) -> Result<(), Error> {
+    let mut chunk = Chunk {
+        first_line: 1,
+        lines: Vec::with_capacity(chunk_lines),
+    };
+    for (i, line) in reader.lines().enumerate() {
+        if failed.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        chunk.lines.push(line.map_err(Error::Io)?);
+        if chunk.lines.len() == chunk_lines {
+            let next = Chunk {
+                first_line: i + 2,
+                lines: Vec::with_capacity(chunk_lines),
+            };
+            send(std::mem::replace(&mut chunk, next));
+        }
+    }
+    if !chunk.lines.is_empty() {
+        send(chunk);
+    }
+    Ok(())
+}
+
+// `generate REPORTS [SEED]`, writing random reports to stdout for trying out
+// huge inputs
+pub fn generate(mut args: impl Iterator<Item = String>) -> io::Result<()> {
+    let reports: usize = args
+        .next()
+        .expect("Missing REPORTS")
+        .parse()
+        .expect("REPORTS must be a number");
+    let seed: u64 = args
+        .next()
+        .map_or(1, |arg| arg.parse().expect("SEED must be a number"));
+
+    let mut rng = Rng::new(seed);
+    let mut out = BufWriter::new(io::stdout().lock());
+    for _ in 0..reports {
+        let report = random_report(&mut rng);
+        let line: Vec<String> = report.iter().map(|level| level.to_string()).collect();
+        writeln!(out, "{}", line.join(" "))?;
+    }
+    out.flush()
+}
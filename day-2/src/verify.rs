@@ -0,0 +1,133 @@
+//! Checks the fast dampener implementations against the brute force one on
+//! random reports, that repairs really are safe and no worse than removing
+//! levels, and that the longest safe subsequences and runs are safe.
+
+use std::process;
+
+use rng::Rng;
+
+use crate::{
+    dampener::{is_safe_with_one_removal, min_removals_to_safe},
+    longest::{longest_safe_run, longest_safe_subsequence},
+    policy::SafetyPolicy,
+    repair::{repair, Repair},
+    report_is_1_away_from_safe,
+};
+
+// A random walk with mostly small steps, so that plenty of reports are safe or
+// one or two levels away from it
+pub fn random_report(rng: &mut Rng) -> Vec<i32> {
+    let len = rng.below(10) as usize;
+    let mut level = rng.below(100) as i32;
+    let direction = if rng.below(2) == 0 { 1 } else { -1 };
+    (0..len)
+        .map(|_| {
+            let step = match rng.below(10) {
+                0 => rng.below(9) as i32 - 4,
+                _ => direction * (1 + rng.below(3) as i32),
+            };
+            level += step;
+            level
+        })
+        .collect()
+}
+
+fn check_repair(report: &[i32], policy: &SafetyPolicy, repair: &Repair) -> Result<(), String> {
+    if !policy.is_safe(&repair.levels) {
+        return Err(format!("repair {:?} isn't safe", repair.levels));
+    }
+    if repair.levels.len() != report.len() - repair.removed.len() {
+        return Err(format!("repair {:?} has the wrong length", repair.levels));
+    }
+    Ok(())
+}
+
+fn check_repairs(report: &[i32], policy: &SafetyPolicy) -> Result<(), String> {
+    let changes_only = repair(report, policy, false);
+    let mixed = repair(report, policy, true);
+    for repair in changes_only.iter().chain(&mixed) {
+        check_repair(report, policy, repair)?;
+    }
+    if changes_only
+        .as_ref()
+        .is_some_and(|repair| !repair.removed.is_empty())
+    {
+        return Err("changes only repair removed levels".to_string());
+    }
+
+    // Mixing in removals can only help, and must do at least as well as only
+    // removing
+    let cost = |repair: &Option<Repair>| repair.as_ref().map(Repair::cost);
+    let removals = min_removals_to_safe(report, policy, report.len());
+    for other in [cost(&changes_only), removals].into_iter().flatten() {
+        if cost(&mixed).is_none_or(|mixed| mixed > other) {
+            return Err(format!(
+                "mixed repair costs {:?}, but {} is possible",
+                cost(&mixed),
+                other
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn check_longest(report: &[i32], policy: &SafetyPolicy) -> Result<(), String> {
+    let subsequence = longest_safe_subsequence(report, policy);
+    if let Some(indices) = &subsequence {
+        let levels: Vec<i32> = indices.iter().map(|&i| report[i]).collect();
+        if !indices.is_sorted_by(|a, b| a < b) || !policy.is_safe(&levels) {
+            return Err(format!("longest subsequence {:?} isn't safe", indices));
+        }
+    }
+    // Keeping the longest subsequence is the fewest removals
+    let removals = min_removals_to_safe(report, policy, report.len());
+    if subsequence.map(|indices| report.len() - indices.len()) != removals {
+        return Err(format!(
+            "longest subsequence doesn't match {:?} removals",
+            removals
+        ));
+    }
+    if let Some(run) = longest_safe_run(report, policy) {
+        let longer_run_safe = (0..report.len().saturating_sub(run.len()))
+            .any(|start| policy.is_safe(&report[start..start + run.len() + 1]));
+        if !policy.is_safe(&report[run.clone()]) || longer_run_safe {
+            return Err(format!("{:?} isn't the longest safe run", run));
+        }
+    }
+    Ok(())
+}
+
+// `verify [COUNT] [SEED]`
+pub fn run(policy: &SafetyPolicy, mut args: impl Iterator<Item = String>) {
+    let count: usize = args
+        .next()
+        .map_or(100_000, |arg| arg.parse().expect("COUNT must be a number"));
+    let seed: u64 = args
+        .next()
+        .map_or(1, |arg| arg.parse().expect("SEED must be a number"));
+
+    let mut rng = Rng::new(seed);
+    let mut mismatches = 0;
+    for _ in 0..count {
+        let report = random_report(&mut rng);
+        let expected = report_is_1_away_from_safe(&report, policy);
+        let linear = is_safe_with_one_removal(&report, policy);
+        let dp = min_removals_to_safe(&report, policy, 1).is_some();
+        if linear != expected || dp != expected {
+            mismatches += 1;
+            eprintln!(
+                "{:?}: brute force {}, linear {}, dp {}",
+                report, expected, linear, dp
+            );
+        }
+        if let Err(problem) = check_repairs(&report, policy).and(check_longest(&report, policy)) {
+            mismatches += 1;
+            eprintln!("{:?}: {}", report, problem);
+        }
+    }
+
+    println!("{} reports checked, {} mismatches", count, mismatches);
+    if mismatches > 0 {
+        process::exit(1);
+    }
+}
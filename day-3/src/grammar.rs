@@ -0,0 +1,112 @@
+use std::fmt;
+
+/// How strictly instructions have to be written to be recognised. The default
+/// is the puzzle's: `name(1,2)` exactly, with operands of 1 to 3 digits.
+#[derive(Clone)]
+pub struct Grammar {
+    pub max_digits: usize,
+    /// Spaces and tabs are allowed after the name and around operands
+    pub whitespace: bool,
+    /// Operands can start with `+` or `-`
+    pub signed: bool,
+    pub case_sensitive: bool,
+    /// Bytes that can go between operands
+    pub separators: Vec<u8>,
+    /// Report sequences that nearly were instructions
+    pub lenient: bool,
+}
+
+impl Default for Grammar {
+    fn default() -> Self {
+        Grammar {
+            max_digits: 3,
+            whitespace: false,
+            signed: false,
+            case_sensitive: true,
+            separators: vec![b','],
+            lenient: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GrammarError(String);
+
+impl fmt::Display for GrammarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+pub const USAGE: &str = "[--max-digits N] [--whitespace] [--signed] [--ignore-case] \
+                         [--separators CHARS] [--lenient]";
+
+// Operands are accumulated in an i128, which every 38 digit number fits in
+const MAX_DIGITS: usize = 38;
+
+impl Grammar {
+    pub fn is_whitespace(&self, byte: u8) -> bool {
+        self.whitespace && (byte == b' ' || byte == b'\t')
+    }
+
+    pub fn is_separator(&self, byte: u8) -> bool {
+        self.separators.contains(&byte)
+    }
+
+    /// Whether the start of `name` is `text`
+    pub fn name_starts_with(&self, name: &str, text: &[u8]) -> bool {
+        name.len() >= text.len() && self.name_matches(&name.as_bytes()[..text.len()], text)
+    }
+
+    pub fn name_matches(&self, name: &[u8], text: &[u8]) -> bool {
+        match self.case_sensitive {
+            true => name == text,
+            false => name.eq_ignore_ascii_case(text),
+        }
+    }
+
+    /// Applies `flag` if it is a grammar flag, taking its value from `args`.
+    /// Returns whether it was one
+    pub fn take_flag(
+        &mut self,
+        flag: &str,
+        args: &mut impl Iterator<Item = String>,
+    ) -> Result<bool, GrammarError> {
+        match flag {
+            "--whitespace" => self.whitespace = true,
+            "--signed" => self.signed = true,
+            "--ignore-case" => self.case_sensitive = false,
+            "--lenient" => self.lenient = true,
+            "--max-digits" => {
+                self.max_digits = args
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .filter(|digits| (1..=MAX_DIGITS).contains(digits))
+                    .ok_or_else(|| {
+                        GrammarError(format!(
+                            "--max-digits needs a number from 1 to {}",
+                            MAX_DIGITS
+                        ))
+                    })?
+            }
+            "--separators" => {
+                let separators = args
+                    .next()
+                    .ok_or_else(|| GrammarError("Missing value for --separators".to_string()))?;
+                if separators.is_empty()
+                    || separators
+                        .bytes()
+                        .any(|byte| byte.is_ascii_digit() || byte == b'(' || byte == b')')
+                {
+                    return Err(GrammarError(format!(
+                        "Invalid separators {:?}, digits and parentheses can't separate operands",
+                        separators
+                    )));
+                }
+                self.separators = separators.into_bytes();
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
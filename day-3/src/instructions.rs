@@ -0,0 +1,296 @@
+//! Instructions that can be found in corrupted memory, and what they do. The
+//! scanner only needs names and arities, so adding an instruction here is
+//! enough for it to be recognised.
+
+use std::{
+    fmt,
+    io::{self, Read},
+};
+
+use crate::{
+    grammar::Grammar,
+    scanner::{Found, NearMiss, Position, Scanner},
+};
+
+/// State instructions act on
+pub struct Machine {
+    /// Signed since operands can be, and `sub` can take it below zero
+    pub total: i128,
+    /// Whether conditional instructions do anything
+    pub enabled: bool,
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Machine {
+            total: 0,
+            enabled: true,
+        }
+    }
+}
+
+pub struct Definition {
+    pub name: &'static str,
+    pub arity: usize,
+    /// Whether operands of the right number of digits are allowed. Anything
+    /// else isn't an instruction, like text the grammar doesn't match
+    pub accepts: fn(&[i128]) -> bool,
+    /// Skipped while the machine is disabled
+    pub conditional: bool,
+    /// None if the total overflows
+    pub execute: fn(&mut Machine, &[i128]) -> Option<()>,
+}
+
+/// Where an instruction was found, with the offset one past its last byte
+#[derive(Clone, Copy, Debug)]
+pub struct Span {
+    pub start: Position,
+    pub end: usize,
+}
+
+/// An instruction found in memory, indexing into the set it was found with
+pub struct Call {
+    pub instruction: usize,
+    pub operands: Vec<i128>,
+    pub span: Span,
+}
+
+/// What `InstructionSet::run_traced` passes on
+pub enum Trace<'a> {
+    Ran(&'a Call, Executed),
+    NearMiss(&'a NearMiss),
+}
+
+/// What running an instruction did
+pub struct Executed {
+    /// Whether the machine was enabled beforehand
+    pub enabled: bool,
+    /// Whether it did anything, which conditional instructions don't while
+    /// the machine is disabled
+    pub ran: bool,
+    /// Change in the total
+    pub contribution: i128,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// The total overflowed running the instruction written here
+    Overflow {
+        instruction: String,
+        at: Position,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{}", err),
+            Error::Overflow { instruction, at } => write!(
+                f,
+                "Total overflowed an i128 running {} at offset {} ({}:{})",
+                instruction, at.offset, at.line, at.column
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+fn any(_: &[i128]) -> bool {
+    true
+}
+
+pub const MUL: Definition = Definition {
+    name: "mul",
+    arity: 2,
+    accepts: any,
+    conditional: true,
+    execute: |machine, operands| {
+        machine.total = machine
+            .total
+            .checked_add(operands[0].checked_mul(operands[1])?)?;
+        Some(())
+    },
+};
+
+pub const DO: Definition = Definition {
+    name: "do",
+    arity: 0,
+    accepts: any,
+    conditional: false,
+    execute: |machine, _| {
+        machine.enabled = true;
+        Some(())
+    },
+};
+
+pub const DONT: Definition = Definition {
+    name: "don't",
+    arity: 0,
+    accepts: any,
+    conditional: false,
+    execute: |machine, _| {
+        machine.enabled = false;
+        Some(())
+    },
+};
+
+pub const ADD: Definition = Definition {
+    name: "add",
+    arity: 2,
+    accepts: any,
+    conditional: true,
+    execute: |machine, operands| {
+        machine.total = machine
+            .total
+            .checked_add(operands[0].checked_add(operands[1])?)?;
+        Some(())
+    },
+};
+
+/// `sub(a,b)` takes `a * b` off the total, undoing `mul(a,b)`
+pub const SUB: Definition = Definition {
+    name: "sub",
+    arity: 2,
+    accepts: any,
+    conditional: true,
+    execute: |machine, operands| {
+        machine.total = machine
+            .total
+            .checked_sub(operands[0].checked_mul(operands[1])?)?;
+        Some(())
+    },
+};
+
+pub const RESET: Definition = Definition {
+    name: "reset",
+    arity: 0,
+    accepts: any,
+    conditional: true,
+    execute: |machine, _| {
+        machine.total = 0;
+        Some(())
+    },
+};
+
+#[derive(Default)]
+pub struct InstructionSet {
+    definitions: Vec<Definition>,
+}
+
+impl InstructionSet {
+    /// Adds an instruction. Names must be unique
+    pub fn with(mut self, definition: Definition) -> Self {
+        assert!(
+            self.lookup(definition.name).is_none(),
+            "{} is already defined",
+            definition.name
+        );
+        self.definitions.push(definition);
+        self
+    }
+
+    /// Part 1 only multiplies
+    pub fn part_1() -> Self {
+        InstructionSet::default().with(MUL)
+    }
+
+    /// Part 2 can turn multiplying off and on
+    pub fn part_2() -> Self {
+        InstructionSet::part_1().with(DO).with(DONT)
+    }
+
+    /// Part 2 plus adding, subtracting and resetting the total
+    pub fn extended() -> Self {
+        InstructionSet::part_2().with(ADD).with(SUB).with(RESET)
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "part-1" => Some(InstructionSet::part_1()),
+            "part-2" => Some(InstructionSet::part_2()),
+            "extended" => Some(InstructionSet::extended()),
+            _ => None,
+        }
+    }
+
+    pub fn definitions(&self) -> &[Definition] {
+        &self.definitions
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<usize> {
+        self.definitions
+            .iter()
+            .position(|definition| definition.name == name)
+    }
+
+    /// `call` as it would be written without any corruption
+    pub fn format(&self, call: &Call) -> String {
+        let operands: Vec<String> = call.operands.iter().map(i128::to_string).collect();
+        format!(
+            "{}({})",
+            self.definitions[call.instruction].name,
+            operands.join(",")
+        )
+    }
+
+    pub fn is_conditional(&self, call: &Call) -> bool {
+        self.definitions[call.instruction].conditional
+    }
+
+    /// Runs `call` unless it is conditional and the machine is disabled, in
+    /// which case its operands aren't even looked at
+    pub fn execute(&self, machine: &mut Machine, call: &Call) -> Result<Executed, Error> {
+        let definition = &self.definitions[call.instruction];
+        let (enabled, before) = (machine.enabled, machine.total);
+        let ran = enabled || !definition.conditional;
+        let overflow = || Error::Overflow {
+            instruction: self.format(call),
+            at: call.span.start,
+        };
+        if ran {
+            (definition.execute)(machine, &call.operands).ok_or_else(overflow)?;
+        }
+        Ok(Executed {
+            enabled,
+            ran,
+            contribution: machine.total.checked_sub(before).ok_or_else(overflow)?,
+        })
+    }
+
+    /// Runs every instruction in `reader` on a fresh machine, printing near
+    /// misses to stderr
+    pub fn run(&self, reader: impl Read, grammar: &Grammar) -> Result<Machine, Error> {
+        self.run_traced(reader, grammar, |trace| {
+            if let Trace::NearMiss(near_miss) = trace {
+                eprintln!("warning: {}", near_miss);
+            }
+        })
+    }
+
+    /// Same as `run`, passing each instruction to `trace` once it has run, and
+    /// each near miss
+    pub fn run_traced(
+        &self,
+        reader: impl Read,
+        grammar: &Grammar,
+        mut trace: impl FnMut(Trace),
+    ) -> Result<Machine, Error> {
+        let mut machine = Machine::default();
+        for found in Scanner::new(reader, self, grammar) {
+            match found? {
+                Found::Call(call) => {
+                    let executed = self.execute(&mut machine, &call)?;
+                    trace(Trace::Ran(&call, executed));
+                }
+                Found::NearMiss(near_miss) => trace(Trace::NearMiss(&near_miss)),
+            }
+        }
+        Ok(machine)
+    }
+}
@@ -0,0 +1,183 @@
+//! Showing where instructions are in the input and what each one did.
+
+use std::{
+    fs::File,
+    io::{self, Read, Write},
+    process,
+};
+
+use grid::{Grid, Layer, Renderer, Style};
+
+use crate::{
+    grammar::Grammar,
+    instructions::{Error, InstructionSet, Trace},
+};
+
+// Takes `--instructions NAME` out of `args`, returning the set and the rest
+pub fn instruction_set(args: impl Iterator<Item = String>) -> (InstructionSet, Vec<String>) {
+    let mut instructions = InstructionSet::part_2();
+    let mut rest = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--instructions" {
+            let name = args.next().expect("Missing value for --instructions");
+            instructions = InstructionSet::by_name(&name).unwrap_or_else(|| {
+                panic!(
+                    "Unknown instruction set {}, expected part-1, part-2 or extended",
+                    name
+                )
+            });
+        } else {
+            rest.push(arg);
+        }
+    }
+    (instructions, rest)
+}
+
+// `list [--instructions NAME] [FILE]`, printing each instruction found with
+// where it is, whether instructions were enabled and what it added
+pub fn list(grammar: &Grammar, args: impl Iterator<Item = String>) -> Result<(), Error> {
+    let (instructions, rest) = instruction_set(args);
+    let path = rest.last().map_or("input_2.txt", String::as_str);
+    let file = File::open(path).expect("File not found");
+
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    writeln!(
+        out,
+        "{:>8} {:>10} {:<16} {:<8} {:>12}",
+        "offset", "line:col", "instruction", "enabled", "contribution"
+    )?;
+    // The first failed write, which has to wait for the scan to finish since
+    // the trace can't stop it
+    let mut written = Ok(());
+    let machine = instructions.run_traced(file, grammar, |trace| {
+        if written.is_err() {
+            return;
+        }
+        written = match trace {
+            Trace::Ran(call, executed) => writeln!(
+                out,
+                "{:>8} {:>10} {:<16} {:<8} {:>12}",
+                call.span.start.offset,
+                format!("{}:{}", call.span.start.line, call.span.start.column),
+                instructions.format(call),
+                if executed.enabled { "yes" } else { "no" },
+                executed.contribution
+            ),
+            Trace::NearMiss(near_miss) => writeln!(
+                out,
+                "{:>8} {:>10} {:<16} near miss",
+                near_miss.span.start.offset,
+                format!(
+                    "{}:{}",
+                    near_miss.span.start.line, near_miss.span.start.column
+                ),
+                format!("{:?}", near_miss.text)
+            ),
+        };
+    })?;
+    written?;
+    writeln!(out, "total {}", machine.total)?;
+    Ok(out.flush()?)
+}
+
+// The input as a grid of lines, padded with spaces to the longest one
+struct Memory {
+    lines: Vec<Vec<u8>>,
+    width: usize,
+}
+
+impl Grid for Memory {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.lines.len()
+    }
+
+    fn cell(&self, i: usize, j: usize) -> char {
+        match self.lines[i].get(j) {
+            Some(byte) if byte.is_ascii_graphic() => *byte as char,
+            Some(_) | None => ' ',
+        }
+    }
+}
+
+// `annotate [--instructions NAME] [--focus I,J] [--viewport WxH] [--color MODE]
+// [FILE]`, showing the input with the instructions found in it highlighted
+pub fn annotate(grammar: &Grammar, args: impl Iterator<Item = String>) -> Result<(), Error> {
+    let (instructions, rest) = instruction_set(args);
+    // Render options are passed on to the renderer along with their values,
+    // leaving the file
+    let mut render_args = Vec::new();
+    let mut path = None;
+    let mut rest = rest.into_iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--focus" | "--viewport" | "--color" => {
+                let value = rest
+                    .next()
+                    .unwrap_or_else(|| panic!("Missing value for {}", arg));
+                render_args.extend([arg, value]);
+            }
+            _ if path.is_none() => path = Some(arg),
+            _ => panic!("Unexpected argument {}", arg),
+        }
+    }
+    let path = path.unwrap_or_else(|| "input_2.txt".to_string());
+    let mut data = Vec::new();
+    File::open(&path)
+        .expect("File not found")
+        .read_to_end(&mut data)
+        .unwrap();
+
+    // Without color, instructions would look the same as the text around them
+    let mut enabled = Layer::new("enabled", Style::Green).ascii('#');
+    let mut disabled = Layer::new("disabled", Style::Red).ascii('-');
+    let mut control = Layer::new("do()/don't()", Style::Yellow).ascii('~');
+    let mut near_misses = Layer::new("near miss", Style::Magenta).ascii('?');
+    instructions.run_traced(&data[..], grammar, |trace| {
+        let (layer, span) = match trace {
+            Trace::Ran(call, executed) => {
+                let layer = match (instructions.is_conditional(call), executed.ran) {
+                    (false, _) => &mut control,
+                    (true, true) => &mut enabled,
+                    (true, false) => &mut disabled,
+                };
+                (layer, call.span)
+            }
+            Trace::NearMiss(near_miss) => (&mut near_misses, near_miss.span),
+        };
+        // A near miss stopped by a newline ends just past its line, which is
+        // never drawn
+        let start = span.start;
+        for column in start.column..start.column + (span.end - start.offset) {
+            layer.push((start.line - 1, column - 1));
+        }
+    })?;
+
+    let text = data.strip_suffix(b"\n").unwrap_or(&data);
+    let lines: Vec<Vec<u8>> = text
+        .split(|&byte| byte == b'\n')
+        .map(<[u8]>::to_vec)
+        .collect();
+    let memory = Memory {
+        width: lines.iter().map(Vec::len).max().unwrap_or(0),
+        lines,
+    };
+    let renderer = Renderer::new(&memory)
+        .layer(enabled)
+        .layer(disabled)
+        .layer(control)
+        .layer(near_misses)
+        .dim_unmarked(true)
+        .apply_args(render_args)
+        .unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            process::exit(2);
+        });
+    let mut out = io::stdout().lock();
+    out.write_all(renderer.render().as_bytes())?;
+    Ok(out.flush()?)
+}
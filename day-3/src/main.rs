@@ -1,73 +1,84 @@
-use regex::Regex;
-use std::{fs::File, io::Read};
+mod grammar;
+mod instructions;
+mod listing;
+mod scanner;
 
-enum Command {
-    Mul(u32, u32),
-    Do,
-    Dont,
-}
+use grammar::Grammar;
+use instructions::{Error, InstructionSet};
+use std::{env, fs::File, io, process};
 
 fn main() {
-    println!("{}", part_1());
-    println!("{}", part_2());
-}
+    let mut args = env::args().skip(1).peekable();
+    let command = args.next_if(|arg| !arg.starts_with("--"));
 
-fn parse_element(element: &str) -> Command {
-    if element.contains("mul") {
-        // m u l ( d ...  dn   )
-        // 0 1 2 3 4 ...  -2  -1
-        let split: Vec<&str> = element[4..element.len() - 1].split(",").collect();
-        let u1: u32 = split[0].parse().unwrap();
-        let u2: u32 = split[1].parse().unwrap();
-        Command::Mul(u1, u2)
-    } else if element == "do()" {
-        Command::Do
-    } else {
-        Command::Dont
+    // Grammar flags apply to every command, anything else is the command's own
+    let mut grammar = Grammar::default();
+    let mut command_args = Vec::new();
+    while let Some(arg) = args.next() {
+        match grammar.take_flag(&arg, &mut args) {
+            Ok(true) => {}
+            Ok(false) => command_args.push(arg),
+            Err(err) => {
+                eprintln!("{}", err);
+                process::exit(2);
+            }
+        }
     }
-}
 
-fn part_1() -> u32 {
-    let mut file = File::open("input_1.txt").expect("File not found");
-    let mut data = String::new();
-    file.read_to_string(&mut data).unwrap();
-
-    let re = Regex::new(r"mul\(\d{1,3},\d{1,3}\)").unwrap();
-    let mut total = 0;
-    for mul in re.find_iter(&data) {
-        if let Command::Mul(u1, u2) = parse_element(mul.as_str()) {
-            total += u1 * u2;
+    match command.as_deref() {
+        None if command_args.is_empty() => {
+            println!("{}", part_1(&grammar).unwrap_or_else(|err| exit_with(err)));
+            println!("{}", part_2(&grammar).unwrap_or_else(|err| exit_with(err)));
         }
+        Some("run") => run(&grammar, command_args.into_iter()),
+        Some("list") => finish(listing::list(&grammar, command_args.into_iter())),
+        Some("annotate") => finish(listing::annotate(&grammar, command_args.into_iter())),
+        _ => usage(),
     }
-    total
 }
 
-fn part_2() -> u32 {
-    let mut file = File::open("input_2.txt").expect("File not found");
-    let mut data = String::new();
-    file.read_to_string(&mut data).unwrap();
-
-    let re = Regex::new(r"mul\(\d{1,3},\d{1,3}\)|don't\(\)|do\(\)").unwrap();
-    let commands = re
-        .find_iter(&data)
-        .map(|match_element| parse_element(match_element.as_str()));
+fn usage() -> ! {
+    eprintln!(
+        "Usage: day-3 [run|list|annotate [--instructions part-1|part-2|extended] [FILE]] {}\n\
+         annotate also takes [--focus I,J] [--viewport WxH] [--color MODE]",
+        grammar::USAGE
+    );
+    process::exit(2);
+}
 
-    let mut do_command = true;
-    let mut total = 0;
+fn exit_with(err: Error) -> ! {
+    eprintln!("{}", err);
+    process::exit(1);
+}
 
-    for command in commands {
-        match command {
-            Command::Mul(u1, u2) => {
-                total += (do_command as u32) * u1 * u2;
-            }
-            Command::Do => {
-                do_command = true;
-            }
-            Command::Dont => {
-                do_command = false;
-            }
-        }
+// Output cut short by whatever it was piped into closing, like `head`, is just
+// an early exit
+fn finish(result: Result<(), Error>) {
+    match result {
+        Err(Error::Io(err)) if err.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(err) => exit_with(err),
+        Ok(()) => {}
     }
+}
 
-    total
+fn part_1(grammar: &Grammar) -> Result<i128, Error> {
+    let file = File::open("input_1.txt").expect("File not found");
+    Ok(InstructionSet::part_1().run(file, grammar)?.total)
+}
+
+fn part_2(grammar: &Grammar) -> Result<i128, Error> {
+    let file = File::open("input_2.txt").expect("File not found");
+    Ok(InstructionSet::part_2().run(file, grammar)?.total)
+}
+
+// `run [--instructions NAME] [FILE]`, printing the total from running FILE
+// (input_2.txt by default) with the named instruction set (part-2 by default)
+fn run(grammar: &Grammar, args: impl Iterator<Item = String>) {
+    let (instructions, rest) = listing::instruction_set(args);
+    let path = rest.last().map_or("input_2.txt", String::as_str);
+    let file = File::open(path).expect("File not found");
+    match instructions.run(file, grammar) {
+        Ok(machine) => println!("{}", machine.total),
+        Err(err) => exit_with(err),
+    }
 }
@@ -0,0 +1,364 @@
+//! Finds instructions in corrupted memory one byte at a time, reading from any
+//! `Read` in chunks. An instruction can straddle chunks, since only the
+//! matcher's state carries over from one byte to the next.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    io::{self, Read},
+};
+
+use crate::{
+    grammar::Grammar,
+    instructions::{Call, InstructionSet, Span},
+};
+
+const CHUNK_SIZE: usize = 8192;
+
+enum State {
+    // Matching the name
+    Name,
+    // A whole name and whitespace after it have been matched, so only more
+    // whitespace or the `(` can follow
+    AfterName {
+        instruction: usize,
+    },
+    // The `(` of the instruction has been matched, along with `digits` digits
+    // of the operand after the ones in `operands`
+    Operands {
+        instruction: usize,
+        operands: Vec<i128>,
+        digits: usize,
+        // Whether the operand being matched has a sign, and whether whitespace
+        // has ended it
+        signed: bool,
+        negative: bool,
+        ended: bool,
+    },
+}
+
+/// Something found in memory
+pub enum Found {
+    Call(Call),
+    /// With a lenient grammar, bytes that looked like the start of an
+    /// instruction up to the byte that stopped them being one
+    NearMiss(NearMiss),
+}
+
+pub struct NearMiss {
+    pub text: String,
+    pub span: Span,
+}
+
+impl fmt::Display for NearMiss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {:?} nearly is an instruction",
+            self.span.start.line, self.span.start.column, self.text
+        )
+    }
+}
+
+enum Step {
+    Matching,
+    Matched(Call),
+    // Whether the attempt nearly matched. The attempt, the last byte of which
+    // broke it, is left in the matcher until it is reset
+    Failed(bool),
+}
+
+/// Where a byte is in the input. Lines and columns count from 1, with columns
+/// in bytes
+#[derive(Clone, Copy, Debug)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    fn start() -> Position {
+        Position {
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn after(self, byte: u8) -> Position {
+        match byte {
+            b'\n' => Position {
+                offset: self.offset + 1,
+                line: self.line + 1,
+                column: 1,
+            },
+            _ => Position {
+                offset: self.offset + 1,
+                column: self.column + 1,
+                ..self
+            },
+        }
+    }
+}
+
+struct Matcher<'a> {
+    instructions: &'a InstructionSet,
+    grammar: &'a Grammar,
+    // Bytes of the instruction being matched so far, and where they are
+    attempt: Vec<u8>,
+    positions: Vec<Position>,
+    state: State,
+}
+
+impl<'a> Matcher<'a> {
+    fn new(instructions: &'a InstructionSet, grammar: &'a Grammar) -> Matcher<'a> {
+        Matcher {
+            instructions,
+            grammar,
+            attempt: Vec::new(),
+            positions: Vec::new(),
+            state: State::Name,
+        }
+    }
+
+    fn step(&mut self, byte: u8, position: Position) -> Step {
+        self.attempt.push(byte);
+        self.positions.push(position);
+        match self.advance(byte) {
+            Some(Some(mut call)) => {
+                call.span = Span {
+                    start: self.positions[0],
+                    end: position.offset + 1,
+                };
+                self.reset();
+                Step::Matched(call)
+            }
+            Some(None) => Step::Matching,
+            None => {
+                // Past the name, or stopped by whitespace right after it
+                let near_miss = match self.state {
+                    State::Name => {
+                        let name = &self.attempt[..self.attempt.len() - 1];
+                        (byte == b' ' || byte == b'\t') && self.complete_name(name).is_some()
+                    }
+                    State::AfterName { .. } | State::Operands { .. } => true,
+                };
+                Step::Failed(near_miss)
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.attempt.clear();
+        self.positions.clear();
+        self.state = State::Name;
+    }
+
+    fn complete_name(&self, name: &[u8]) -> Option<usize> {
+        self.instructions
+            .definitions()
+            .iter()
+            .position(|definition| self.grammar.name_matches(definition.name.as_bytes(), name))
+    }
+
+    // State once the `(` of `instruction` has been matched
+    fn operands(&self, instruction: usize) -> State {
+        // Digits go into the last operand, so start the first
+        let operands = match self.instructions.definitions()[instruction].arity {
+            0 => Vec::new(),
+            _ => vec![0],
+        };
+        State::Operands {
+            instruction,
+            operands,
+            digits: 0,
+            signed: false,
+            negative: false,
+            ended: false,
+        }
+    }
+
+    // None if `byte` can't continue the instruction, otherwise whether it
+    // finished one
+    fn advance(&mut self, byte: u8) -> Option<Option<Call>> {
+        let grammar = self.grammar;
+        match &mut self.state {
+            State::Name => {
+                let name = &self.attempt[..self.attempt.len() - 1];
+                if byte == b'(' {
+                    self.state = self.operands(self.complete_name(name)?);
+                } else if grammar.is_whitespace(byte) && !name.is_empty() {
+                    let instruction = self.complete_name(name)?;
+                    self.state = State::AfterName { instruction };
+                } else if !self
+                    .instructions
+                    .definitions()
+                    .iter()
+                    .any(|definition| grammar.name_starts_with(definition.name, &self.attempt))
+                {
+                    return None;
+                }
+                Some(None)
+            }
+            &mut State::AfterName { instruction } => {
+                if byte == b'(' {
+                    self.state = self.operands(instruction);
+                    Some(None)
+                } else {
+                    grammar.is_whitespace(byte).then_some(None)
+                }
+            }
+            State::Operands {
+                instruction,
+                operands,
+                digits,
+                signed,
+                negative,
+                ended,
+            } => {
+                let definition = &self.instructions.definitions()[*instruction];
+                let arity = definition.arity;
+                match byte {
+                    b'0'..=b'9'
+                        if !operands.is_empty() && !*ended && *digits < grammar.max_digits =>
+                    {
+                        let operand = operands.last_mut().unwrap();
+                        let digit = i128::from(byte - b'0');
+                        *operand = match negative {
+                            true => *operand * 10 - digit,
+                            false => *operand * 10 + digit,
+                        };
+                        *digits += 1;
+                        Some(None)
+                    }
+                    b'+' | b'-' if grammar.signed && !operands.is_empty() && *digits == 0 => {
+                        if *signed {
+                            return None;
+                        }
+                        *signed = true;
+                        *negative = byte == b'-';
+                        Some(None)
+                    }
+                    // Around operands and separators, but not inside an operand
+                    _ if grammar.is_whitespace(byte) && (*digits > 0 || !*signed) => {
+                        *ended = *digits > 0;
+                        Some(None)
+                    }
+                    _ if grammar.is_separator(byte) && *digits > 0 && operands.len() < arity => {
+                        operands.push(0);
+                        (*digits, *signed, *negative, *ended) = (0, false, false, false);
+                        Some(None)
+                    }
+                    b')' if operands.len() == arity
+                        && (arity == 0 || *digits > 0)
+                        && (definition.accepts)(operands) =>
+                    {
+                        // The span is filled in by `step`
+                        Some(Some(Call {
+                            instruction: *instruction,
+                            operands: std::mem::take(operands),
+                            span: Span {
+                                start: Position::start(),
+                                end: 0,
+                            },
+                        }))
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+/// The instructions in `reader`, in order. Like a regex, instructions don't
+/// overlap, and after bytes that only look like the start of one, scanning
+/// picks up again from the byte after that start.
+pub struct Scanner<'a, R> {
+    reader: R,
+    chunk: Box<[u8]>,
+    // Unread part of `chunk`
+    start: usize,
+    end: usize,
+    // Where the next byte of `chunk` is
+    position: Position,
+    // Bytes from a failed attempt to go through again
+    replay: VecDeque<(u8, Position)>,
+    matcher: Matcher<'a>,
+}
+
+impl<'a, R: Read> Scanner<'a, R> {
+    /// Scans for instructions in `instructions` written as `grammar` allows
+    pub fn new(reader: R, instructions: &'a InstructionSet, grammar: &'a Grammar) -> Self {
+        Scanner {
+            reader,
+            chunk: vec![0; CHUNK_SIZE].into_boxed_slice(),
+            start: 0,
+            end: 0,
+            position: Position::start(),
+            replay: VecDeque::new(),
+            matcher: Matcher::new(instructions, grammar),
+        }
+    }
+
+    fn next_byte(&mut self) -> io::Result<Option<(u8, Position)>> {
+        if let Some(replayed) = self.replay.pop_front() {
+            return Ok(Some(replayed));
+        }
+        if self.start == self.end {
+            self.end = loop {
+                match self.reader.read(&mut self.chunk) {
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    read => break read?,
+                }
+            };
+            self.start = 0;
+            if self.end == 0 {
+                return Ok(None);
+            }
+        }
+        let byte = self.chunk[self.start];
+        let position = self.position;
+        self.start += 1;
+        self.position = position.after(byte);
+        Ok(Some((byte, position)))
+    }
+}
+
+impl<R: Read> Iterator for Scanner<'_, R> {
+    type Item = io::Result<Found>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (byte, position) = match self.next_byte() {
+                Ok(Some(next)) => next,
+                // An unfinished instruction at the end is just corruption
+                Ok(None) => return None,
+                Err(err) => return Some(Err(err)),
+            };
+            match self.matcher.step(byte, position) {
+                Step::Matching => {}
+                Step::Matched(call) => return Some(Ok(Found::Call(call))),
+                Step::Failed(near_miss) => {
+                    let matcher = &mut self.matcher;
+                    let near_miss = (near_miss && matcher.grammar.lenient).then(|| NearMiss {
+                        text: String::from_utf8_lossy(&matcher.attempt).into_owned(),
+                        span: Span {
+                            start: matcher.positions[0],
+                            end: position.offset + 1,
+                        },
+                    });
+                    // Most failures are a single byte, leaving nothing to replay
+                    let replayed = matcher.attempt[1..].iter().zip(&matcher.positions[1..]);
+                    for (&byte, &position) in replayed.rev() {
+                        self.replay.push_front((byte, position));
+                    }
+                    matcher.reset();
+                    if let Some(near_miss) = near_miss {
+                        return Some(Ok(Found::NearMiss(near_miss)));
+                    }
+                }
+            }
+        }
+    }
+}
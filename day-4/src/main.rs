@@ -1,6 +1,19 @@
-use std::{fs::File, io::Read};
+use grid::{Grid, Layer, Renderer, Style};
+use std::{env, fs::File, io::Read, process};
 
 fn main() {
+    let mut args = env::args().skip(1);
+    if let Some(command) = args.next() {
+        match command.as_str() {
+            "render" => render(args),
+            _ => {
+                eprintln!("Usage: day-4 [render [--focus I,J] [--viewport WxH] [--color MODE]]");
+                process::exit(2);
+            }
+        }
+        return;
+    }
+
     println!("{}", part_1());
     println!("{}", part_2());
 }
@@ -40,6 +53,20 @@ impl Board {
     }
 }
 
+impl Grid for Board {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn cell(&self, i: usize, j: usize) -> char {
+        self.data[i * self.width + j]
+    }
+}
+
 fn is_xmas_from(board: &Board, i: usize, j: usize, dir: Direction) -> bool {
     let i = i as i64;
     let j = j as i64;
@@ -47,13 +74,12 @@ fn is_xmas_from(board: &Board, i: usize, j: usize, dir: Direction) -> bool {
     ['X', 'M', 'A', 'S']
         .iter()
         .enumerate()
-        .map(|(dir_step, target_letter)| {
+        .all(|(dir_step, target_letter)| {
             let dir_step = dir_step as i64;
             board
                 .get(i + dir_step * dir.0, j + dir_step * dir.1)
-                .map_or(false, |letter| letter == *target_letter)
+                .is_some_and(|letter| letter == *target_letter)
         })
-        .all(|equals| equals)
 }
 
 fn is_x_shape_mas_from(board: &Board, i: usize, j: usize) -> bool {
@@ -112,3 +138,48 @@ fn part_2() -> u32 {
     }
     num_xmas_found
 }
+
+fn render(args: impl Iterator<Item = String>) {
+    let mut file = File::open("input_1.txt").expect("File not found");
+    let mut data = String::new();
+    file.read_to_string(&mut data).unwrap();
+
+    let board = Board::from_str(&data);
+
+    // Highlight the letters of every XMAS (part 1) and X-MAS (part 2) found
+    // Without color, the letters would look the same as the rest of the board
+    let mut xmas = Layer::new("XMAS", Style::Green).ascii('#');
+    let mut x_mas = Layer::new("X-MAS", Style::Yellow).ascii('x');
+    for i in 0..board.height {
+        for j in 0..board.width {
+            for step_x in [-1, 0, 1] {
+                for step_y in [-1, 0, 1] {
+                    if is_xmas_from(&board, i, j, Direction(step_x, step_y)) {
+                        for step in 0..4 {
+                            xmas.push((
+                                (i as i64 + step * step_x) as usize,
+                                (j as i64 + step * step_y) as usize,
+                            ));
+                        }
+                    }
+                }
+            }
+            if is_x_shape_mas_from(&board, i, j) {
+                for (di, dj) in [(0, 0), (-1, -1), (-1, 1), (1, -1), (1, 1)] {
+                    x_mas.push(((i as i64 + di) as usize, (j as i64 + dj) as usize));
+                }
+            }
+        }
+    }
+
+    let renderer = Renderer::new(&board)
+        .layer(xmas)
+        .layer(x_mas)
+        .dim_unmarked(true)
+        .apply_args(args)
+        .unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            process::exit(2);
+        });
+    print!("{}", renderer.render());
+}
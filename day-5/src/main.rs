@@ -1,4 +1,6 @@
-use std::{cmp::Ordering, collections::HashMap, fs::File, io::Read, str::FromStr};
+use graph::Graph;
+use parsing::{Cursor, ParseError, Separator};
+use std::{collections::HashMap, fs::File, io::Read, str::FromStr};
 
 fn main() {
     println!("{}", part_1());
@@ -6,33 +8,40 @@ fn main() {
 }
 
 struct PagesInfo {
-    ordering_rules: Vec<(i32, i32)>,
+    // Edge `before -> after` for each rule
+    ordering_rules: Graph<i32>,
     update_pages: Vec<Vec<i32>>,
 }
 
 impl FromStr for PagesInfo {
-    type Err = ();
+    type Err = ParseError;
 
+    // "before|after" rules, a blank line, then "page,page,..." updates
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut ordering_rules: Vec<(i32, i32)> = Vec::new();
-        let mut update_pages: Vec<Vec<i32>> = Vec::new();
-        s.split_whitespace().for_each(|line| {
-            if line.contains("|") {
-                // Ordering rule
-                let rule_orders: Vec<i32> = line
-                    .split("|")
-                    .map(|num| num.parse::<i32>().unwrap())
-                    .collect();
-                assert_eq!(rule_orders.len(), 2);
-                ordering_rules.push((rule_orders[0], rule_orders[1]));
-            } else {
-                let updates: Vec<i32> = line
-                    .split(",")
-                    .map(|num| num.parse::<i32>().unwrap())
-                    .collect();
-                update_pages.push(updates);
-            }
-        });
+        let sections = parsing::sections(s);
+        let [rules, updates] = &sections[..] else {
+            return Err(Cursor::new(s).error(format!(
+                "expected 2 sections separated by a blank line, found {}",
+                sections.len()
+            )));
+        };
+
+        let mut ordering_rules: Graph<i32> = Graph::new();
+        for line in rules {
+            let (before, after) = parsing::pair(&mut line.clone(), '|')?;
+            ordering_rules.add_edge(before, after);
+        }
+
+        let mut update_pages: Vec<Vec<i32>> = Vec::with_capacity(updates.len());
+        for line in updates {
+            let updates: Vec<i32> = parsing::integer_list(&mut line.clone(), Separator::Char(','))?;
+            // Pages without any rules still need to be in the graph to be sorted
+            updates
+                .iter()
+                .for_each(|page| ordering_rules.add_node(*page));
+            update_pages.push(updates);
+        }
+
         Ok(PagesInfo {
             ordering_rules,
             update_pages,
@@ -50,17 +59,14 @@ fn update_page_to_index_map(update_page: &[i32]) -> HashMap<i32, usize> {
 
 fn is_correctly_ordered_update(pages_info: &PagesInfo, update_page: &[i32]) -> bool {
     let index_map = update_page_to_index_map(update_page);
-    for (before, after) in &pages_info.ordering_rules {
-        if let Some(before_idx) = index_map.get(before) {
-            if let Some(after_idx) = index_map.get(after) {
-                // Check if ee broke a rule
-                if before_idx >= after_idx {
-                    return false;
-                }
-            }
-        }
-    }
-    true
+    update_page.iter().enumerate().all(|(before_idx, before)| {
+        // Check if we broke a rule
+        pages_info
+            .ordering_rules
+            .successors(*before)
+            .filter_map(|after| index_map.get(&after))
+            .all(|after_idx| before_idx < *after_idx)
+    })
 }
 
 fn part_1() -> i32 {
@@ -90,28 +96,14 @@ fn part_2() -> i32 {
         .iter()
         // Filter out any pages that don't follow the ordering rules
         .filter(|update_page| !is_correctly_ordered_update(&pages_info, update_page))
-        // Correct them to be properly ordered
+        // Correct them to be properly ordered. The full rule set has cycles,
+        // but the rules between the pages of a single update don't
         .map(|update_page| {
-            let mut update_page = update_page.clone();
-            update_page.sort_by(|a, b| {
-                // Sort by ordering rules
-                let exists_rule_a_before_b = pages_info
-                    .ordering_rules
-                    .iter()
-                    .any(|(before, after)| (before, after) == (a, b));
-                let exists_rule_b_before_a = pages_info
-                    .ordering_rules
-                    .iter()
-                    .any(|(before, after)| (before, after) == (b, a));
-                if exists_rule_a_before_b {
-                    Ordering::Less
-                } else if exists_rule_b_before_a {
-                    Ordering::Greater
-                } else {
-                    Ordering::Less
-                }
-            });
-            update_page
+            pages_info
+                .ordering_rules
+                .induced_subgraph(update_page.iter().copied())
+                .topological_sort()
+                .expect("Ordering rules for an update are cyclic")
         })
         // Get the middle page number
         .map(|update_page| update_page[update_page.len() / 2])
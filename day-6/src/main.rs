@@ -1,16 +1,15 @@
-use std::{collections::HashSet, fs::File, io::Read};
+mod verify;
 
+use grid::{BitGrid, DirectionalBitGrid, Grid, Layer, Renderer, Style};
+use std::{env, fs::File, io::Read, process};
+
+#[derive(Clone, Copy)]
 enum BoardTile {
     Empty,
     Blocked,
 }
 
-struct BoardPosition {
-    visited: bool,
-    tile: BoardTile,
-}
-
-#[derive(Clone, Copy, Hash, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 enum Direction {
     Up = 0,
     Down = 1,
@@ -18,8 +17,11 @@ enum Direction {
     Right = 3,
 }
 
+type PlayerState = ((i64, i64), Direction);
+
 struct Board {
-    data: Vec<BoardPosition>,
+    data: Vec<BoardTile>,
+    visited: BitGrid,
     width: usize,
     height: usize,
     player_start_position: (i64, i64),
@@ -34,32 +36,24 @@ impl Board {
         let height = lines.len();
 
         let chars: Vec<char> = lines.iter().flat_map(|line| line.chars()).collect();
-        let mut data: Vec<BoardPosition> = chars
+        let data: Vec<BoardTile> = chars
             .iter()
             .map(|c| match c {
-                '.' => BoardPosition {
-                    visited: false,
-                    tile: BoardTile::Empty,
-                },
-                '^' => BoardPosition {
-                    visited: false,
-                    tile: BoardTile::Empty,
-                },
-                _ => BoardPosition {
-                    visited: false,
-                    tile: BoardTile::Blocked,
-                },
+                '.' | '^' => BoardTile::Empty,
+                _ => BoardTile::Blocked,
             })
             .collect();
         let start_index = chars
             .iter()
             .position(|board_position| *board_position == '^')
             .unwrap() as i64;
-        data[start_index as usize].visited = true;
 
         let start_position = (start_index / width as i64, start_index % width as i64);
+        let mut visited = BitGrid::new(width, height);
+        visited.insert(start_position.0 as usize, start_position.1 as usize);
         Board {
             data,
+            visited,
             width,
             height,
             player_start_position: start_position,
@@ -68,7 +62,7 @@ impl Board {
         }
     }
 
-    fn get_mut(&mut self, i: i64, j: i64) -> Option<&mut BoardPosition> {
+    fn get(&self, i: i64, j: i64) -> Option<BoardTile> {
         if i < 0 || j < 0 {
             return None;
         }
@@ -79,14 +73,15 @@ impl Board {
             return None;
         }
 
-        Some(&mut self.data[i * self.width + j])
+        Some(self.data[i * self.width + j])
     }
 
     fn player_on_board(&self) -> bool {
-        self.player_position.0 >= 0
-            && self.player_position.0 < self.height as i64
-            && self.player_position.1 >= 0
-            && self.player_position.1 < self.width as i64
+        self.on_board(self.player_position)
+    }
+
+    fn on_board(&self, (i, j): (i64, i64)) -> bool {
+        i >= 0 && i < self.height as i64 && j >= 0 && j < self.width as i64
     }
 
     fn right_of(dir: Direction) -> Direction {
@@ -98,69 +93,108 @@ impl Board {
         }
     }
 
-    fn step(&mut self) {
-        let i_step = match self.player_direction {
+    // Where a player at `position` facing `direction` ends up after one step,
+    // which may be off the board
+    fn next_state(&self, position: (i64, i64), direction: Direction) -> PlayerState {
+        let i_step = match direction {
             Direction::Up => -1,
             Direction::Down => 1,
             _ => 0,
         };
-        let j_step = match self.player_direction {
+        let j_step = match direction {
             Direction::Right => 1,
             Direction::Left => -1,
             _ => 0,
         };
 
-        let next_i = self.player_position.0 + i_step;
-        let next_j = self.player_position.1 + j_step;
-
-        if let Some(next_position) = self.get_mut(next_i, next_j) {
-            match next_position.tile {
-                BoardTile::Blocked => {
-                    // Turn and try again
-                    self.player_direction = Board::right_of(self.player_direction);
-                    self.step(); // recurse max 2 times with these boards
-                }
-                BoardTile::Empty => {
-                    // Move forward
-                    next_position.visited = true;
-                    self.player_position = (next_i, next_j);
-                }
+        let next_i = position.0 + i_step;
+        let next_j = position.1 + j_step;
+
+        match self.get(next_i, next_j) {
+            Some(BoardTile::Blocked) => {
+                // Turn and try again, recursing max 2 times with these boards
+                self.next_state(position, Board::right_of(direction))
             }
-        } else {
-            // Off board
-            self.player_position = (next_i, next_j);
+            // Move forward, or off board
+            Some(BoardTile::Empty) | None => ((next_i, next_j), direction),
         }
     }
 
-    fn reset(&mut self) {
-        for board_position in &mut self.data {
-            board_position.visited = false;
+    fn step(&mut self) {
+        let (position, direction) = self.next_state(self.player_position, self.player_direction);
+        self.player_position = position;
+        self.player_direction = direction;
+        if self.player_on_board() {
+            self.visited
+                .insert(position.0 as usize, position.1 as usize);
         }
+    }
+
+    fn reset(&mut self) {
+        self.visited.clear();
+        self.visited.insert(
+            self.player_start_position.0 as usize,
+            self.player_start_position.1 as usize,
+        );
         self.player_position = self.player_start_position;
         self.player_direction = Direction::Up;
     }
 }
 
-fn is_stuck_in_loop(board: &mut Board) -> bool {
-    let mut visited: HashSet<(i64, i64, Direction)> = HashSet::new();
+impl Grid for Board {
+    fn width(&self) -> usize {
+        self.width
+    }
 
-    while board.player_on_board() {
-        let position_info = (
-            board.player_position.0,
-            board.player_position.1,
-            board.player_direction,
-        );
-        if visited.contains(&position_info) {
-            return true;
-        }
+    fn height(&self) -> usize {
+        self.height
+    }
 
-        visited.insert(position_info);
-        board.step();
+    fn cell(&self, i: usize, j: usize) -> char {
+        if (i as i64, j as i64) == self.player_start_position {
+            return '^';
+        }
+        match self.data[i * self.width + j] {
+            BoardTile::Empty => '.',
+            BoardTile::Blocked => '#',
+        }
     }
-    false
+}
+
+// `seen` is scratch space for the (position, direction) states already passed
+// through, reused between calls to avoid reallocating it
+fn is_stuck_in_loop(board: &Board, seen: &mut DirectionalBitGrid) -> bool {
+    seen.clear();
+
+    let start = (board.player_position, board.player_direction);
+    cycle::find_repeat(
+        start,
+        |&(position, direction)| {
+            let next = board.next_state(position, direction);
+            board.on_board(next.0).then_some(next)
+        },
+        |&((i, j), direction)| seen.insert(i as usize, j as usize, direction as usize),
+    )
+    .is_some()
 }
 
 fn main() {
+    let mut args = env::args().skip(1);
+    if let Some(command) = args.next() {
+        match command.as_str() {
+            "render" => render(args),
+            "verify" => verify::run(args),
+            _ => {
+                eprintln!(
+                    "Usage: day-6 [render [--focus I,J] [--viewport WxH] [--color MODE] \
+                     | verify [COUNT] [SEED]]"
+                );
+                process::exit(2);
+            }
+        }
+        return;
+    }
+
     println!("{}", part_1());
     println!("{}", part_2());
 }
@@ -177,11 +211,7 @@ fn part_1() -> usize {
         board.step();
     }
 
-    board
-        .data
-        .iter()
-        .filter(|board_position| board_position.visited)
-        .count()
+    board.visited.count_ones()
 }
 
 fn part_2() -> usize {
@@ -190,33 +220,79 @@ fn part_2() -> usize {
     file.read_to_string(&mut data).unwrap();
 
     let mut board = Board::from_str(&data);
+    loop_obstacle_indexes(&mut board).len()
+}
 
+// Indexes of the tiles where a new obstacle would trap the player in a loop
+fn loop_obstacle_indexes(board: &mut Board) -> Vec<usize> {
     // Map out the path and find visited indexes since only putting an obstacle
     // on one of those would affect our path and cause a loop
     while board.player_on_board() {
         board.step();
     }
     let visited_idxs: Vec<usize> = board
-        .data
-        .iter()
-        .enumerate()
-        .filter(|(_, board_position)| board_position.visited)
-        .map(|(i, _)| i)
+        .visited
+        .iter_ones()
+        .map(|(i, j)| i * board.width + j)
         .collect();
 
     board.reset();
 
     // Try blocking each tile along their original path, checking for making a loop
-    let mut num_can_be_made_loop = 0;
+    let mut seen = DirectionalBitGrid::new(board.width, board.height);
+    let mut loop_obstacles = Vec::new();
     for i in visited_idxs {
-        board.data[i].tile = BoardTile::Blocked;
+        board.data[i] = BoardTile::Blocked;
 
-        num_can_be_made_loop += is_stuck_in_loop(&mut board) as usize;
+        if is_stuck_in_loop(board, &mut seen) {
+            loop_obstacles.push(i);
+        }
 
-        // Undo and reset board
-        board.reset();
-        board.data[i].tile = BoardTile::Empty;
+        // Undo
+        board.data[i] = BoardTile::Empty;
+    }
+
+    loop_obstacles
+}
+
+fn render(args: impl Iterator<Item = String>) {
+    let mut file = File::open("input_1.txt").expect("File not found");
+    let mut data = String::new();
+    file.read_to_string(&mut data).unwrap();
+
+    let mut board = Board::from_str(&data);
+    let width = board.width;
+    let start = (
+        board.player_start_position.0 as usize,
+        board.player_start_position.1 as usize,
+    );
+
+    let obstacles = Layer::new("Loop-causing obstacle", Style::Red)
+        .ascii('O')
+        .cells(
+            loop_obstacle_indexes(&mut board)
+                .into_iter()
+                .map(|i| (i / width, i % width)),
+        );
+
+    let mut path = Vec::new();
+    while board.player_on_board() {
+        path.push((
+            board.player_position.0 as usize,
+            board.player_position.1 as usize,
+        ));
+        board.step();
     }
 
-    num_can_be_made_loop
+    let renderer = Renderer::new(&board)
+        .path("Guard path", Style::Cyan, path)
+        .layer(obstacles)
+        .layer(Layer::new("Start", Style::Bold).ascii('^').cells([start]))
+        .focus(start)
+        .apply_args(args)
+        .unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            process::exit(2);
+        });
+    print!("{}", renderer.render());
 }
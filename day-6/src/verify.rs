@@ -0,0 +1,116 @@
+//! Checks the cycle detection strategies against each other and a brute force
+//! walk on small random maps, some of which halt.
+
+use std::process;
+
+use cycle::{detect, find_repeat, Cycle, Strategy};
+use rng::Rng;
+
+// x -> (a * x * x + b * x + c) mod n, halting on reaching `halt` if it is set
+struct Map {
+    n: u64,
+    a: u64,
+    b: u64,
+    c: u64,
+    halt: Option<u64>,
+}
+
+impl Map {
+    fn random(rng: &mut Rng) -> Map {
+        let n = 1 + rng.below(50);
+        Map {
+            n,
+            a: rng.below(n),
+            b: rng.below(n),
+            c: rng.below(n),
+            halt: (rng.below(4) == 0).then(|| rng.below(n)),
+        }
+    }
+
+    fn step(&self, x: &u64) -> Option<u64> {
+        (self.halt != Some(*x)).then(|| (self.a * x * x + self.b * x + self.c) % self.n)
+    }
+}
+
+// (tail_len, cycle_len, first_repeated) found by remembering when each state
+// was visited
+fn brute_force(start: u64, step: impl Fn(&u64) -> Option<u64>) -> Option<(usize, usize, u64)> {
+    let mut visited_at = Vec::new();
+    let mut state = start;
+    loop {
+        if let Some(index) = visited_at.iter().position(|&visited| visited == state) {
+            return Some((index, visited_at.len() - index, state));
+        }
+        visited_at.push(state);
+        state = step(&state)?;
+    }
+}
+
+fn check(start: u64, step: impl Fn(&u64) -> Option<u64>) -> Result<(), String> {
+    let expected = brute_force(start, &step);
+    let summary = |cycle: Option<Cycle<u64>>| {
+        cycle.map(|cycle| (cycle.tail_len, cycle.cycle_len, cycle.first_repeated))
+    };
+    for strategy in [Strategy::HashSet, Strategy::Floyd, Strategy::Brent] {
+        let found = summary(detect(start, &step, strategy));
+        if found != expected {
+            let name = match strategy {
+                Strategy::HashSet => "hash set",
+                Strategy::Floyd => "Floyd",
+                Strategy::Brent => "Brent",
+            };
+            return Err(format!(
+                "{} found {:?}, brute force {:?}",
+                name, found, expected
+            ));
+        }
+    }
+    let mut seen = Vec::new();
+    let repeat = find_repeat(start, &step, |&state| {
+        let new = !seen.contains(&state);
+        seen.push(state);
+        new
+    });
+    if repeat != expected.map(|(_, _, first_repeated)| first_repeated) {
+        return Err(format!(
+            "find_repeat found {:?}, brute force {:?}",
+            repeat, expected
+        ));
+    }
+    Ok(())
+}
+
+// `verify [COUNT] [SEED]`
+pub fn run(mut args: impl Iterator<Item = String>) {
+    let count: usize = args
+        .next()
+        .map_or(100_000, |arg| arg.parse().expect("COUNT must be a number"));
+    let seed: u64 = args
+        .next()
+        .map_or(1, |arg| arg.parse().expect("SEED must be a number"));
+
+    let mut mismatches = 0;
+    // Counting down to 0 always halts
+    if let Err(problem) = check(10, |&x| x.checked_sub(1)) {
+        mismatches += 1;
+        eprintln!("countdown: {}", problem);
+    }
+
+    let mut rng = Rng::new(seed);
+    for _ in 0..count {
+        let map = Map::random(&mut rng);
+        let start = rng.below(map.n);
+        if let Err(problem) = check(start, |x| map.step(x)) {
+            mismatches += 1;
+            eprintln!(
+                "x -> ({} x^2 + {} x + {}) mod {} halting at {:?}, from {}: {}",
+                map.a, map.b, map.c, map.n, map.halt, start, problem
+            );
+        }
+    }
+
+    println!("{} maps checked, {} mismatches", count + 1, mismatches);
+    if mismatches > 0 {
+        process::exit(1);
+    }
+}
@@ -1,4 +1,5 @@
 use itertools::Itertools;
+use parsing::{Cursor, ParseError};
 use std::{
     fs::File,
     io::{BufRead, BufReader},
@@ -44,23 +45,19 @@ impl Equation {
 }
 
 impl FromStr for Equation {
-    type Err = ();
+    type Err = ParseError;
 
     // "target: num1 num2 num3" etc...
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let equation: Vec<u64> = s
-            .split_whitespace()
-            .enumerate()
-            .map(|(i, section)| {
-                match i {
-                    0 => section[..section.len() - 1].parse::<u64>().unwrap(), // remove ":"
-                    _ => section.parse::<u64>().unwrap(),
-                }
-            })
-            .collect();
-
-        assert!(equation.len() >= 3);
-
+        let mut cursor = Cursor::new(s);
+        let (target, operands) = parsing::labelled::<u64>(&mut cursor)?;
+        if operands.len() < 2 {
+            return Err(cursor.error("expected at least 2 operands"));
+        }
+
+        let mut equation = Vec::with_capacity(operands.len() + 1);
+        equation.push(target);
+        equation.extend(operands);
         Ok(Equation(equation))
     }
 }
@@ -91,9 +88,12 @@ fn part_1() -> u64 {
     let file = File::open("input_1.txt").expect("File not found");
     let reader = BufReader::new(file);
 
-    let equations = reader
-        .lines()
-        .map(|line| line.unwrap().parse::<Equation>().unwrap());
+    let equations = reader.lines().enumerate().map(|(i, line)| {
+        line.unwrap()
+            .parse::<Equation>()
+            .map_err(|err| err.at_line(i + 1))
+            .unwrap()
+    });
 
     sum_of_satisfiable_equations(equations, &[Operator::Plus, Operator::Times])
 }
@@ -102,9 +102,12 @@ fn part_2() -> u64 {
     let file = File::open("input_1.txt").expect("File not found");
     let reader = BufReader::new(file);
 
-    let equations = reader
-        .lines()
-        .map(|line| line.unwrap().parse::<Equation>().unwrap());
+    let equations = reader.lines().enumerate().map(|(i, line)| {
+        line.unwrap()
+            .parse::<Equation>()
+            .map_err(|err| err.at_line(i + 1))
+            .unwrap()
+    });
 
     use std::time::Instant;
     let now = Instant::now();
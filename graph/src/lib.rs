@@ -0,0 +1,313 @@
+//! Directed graphs as adjacency lists over arbitrary node ids.
+//!
+//! Nodes are stored in insertion order and every traversal visits them in that
+//! order, so results are deterministic.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+#[derive(Clone)]
+pub struct Graph<N> {
+    nodes: Vec<N>,
+    index: HashMap<N, usize>,
+    // Outgoing (target index, weight) per node index
+    edges: Vec<Vec<(usize, u64)>>,
+}
+
+impl<N: Copy + Eq + Hash> Default for Graph<N> {
+    fn default() -> Self {
+        Graph::new()
+    }
+}
+
+impl<N: Copy + Eq + Hash> FromIterator<(N, N)> for Graph<N> {
+    fn from_iter<T: IntoIterator<Item = (N, N)>>(edges: T) -> Self {
+        let mut graph = Graph::new();
+        for (from, to) in edges {
+            graph.add_edge(from, to);
+        }
+        graph
+    }
+}
+
+impl<N: Copy + Eq + Hash> Graph<N> {
+    pub fn new() -> Graph<N> {
+        Graph {
+            nodes: Vec::new(),
+            index: HashMap::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    /// Adds the node if it isn't present yet
+    pub fn add_node(&mut self, node: N) {
+        self.index_of_or_insert(node);
+    }
+
+    /// Adds an edge with weight 1
+    pub fn add_edge(&mut self, from: N, to: N) {
+        self.add_weighted_edge(from, to, 1);
+    }
+
+    /// Adds an edge, replacing the weight of an existing one
+    pub fn add_weighted_edge(&mut self, from: N, to: N, weight: u64) {
+        let from = self.index_of_or_insert(from);
+        let to = self.index_of_or_insert(to);
+        match self.edges[from].iter_mut().find(|(target, _)| *target == to) {
+            Some(edge) => edge.1 = weight,
+            None => self.edges[from].push((to, weight)),
+        }
+    }
+
+    fn index_of_or_insert(&mut self, node: N) -> usize {
+        *self.index.entry(node).or_insert_with(|| {
+            self.nodes.push(node);
+            self.edges.push(Vec::new());
+            self.nodes.len() - 1
+        })
+    }
+
+    pub fn contains(&self, node: N) -> bool {
+        self.index.contains_key(&node)
+    }
+
+    pub fn has_edge(&self, from: N, to: N) -> bool {
+        match (self.index.get(&from), self.index.get(&to)) {
+            (Some(&from), Some(&to)) => self.edges[from].iter().any(|(target, _)| *target == to),
+            _ => false,
+        }
+    }
+
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (N, N, u64)> + '_ {
+        self.edges.iter().enumerate().flat_map(move |(from, targets)| {
+            targets
+                .iter()
+                .map(move |&(to, weight)| (self.nodes[from], self.nodes[to], weight))
+        })
+    }
+
+    pub fn successors(&self, node: N) -> impl Iterator<Item = N> + '_ {
+        self.index
+            .get(&node)
+            .into_iter()
+            .flat_map(move |&from| self.edges[from].iter().map(|&(to, _)| self.nodes[to]))
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The graph restricted to `nodes` (those present) and the edges between them
+    pub fn induced_subgraph(&self, nodes: impl IntoIterator<Item = N>) -> Graph<N> {
+        let mut subgraph = Graph::new();
+        for node in nodes.into_iter().filter(|node| self.contains(*node)) {
+            subgraph.add_node(node);
+        }
+        for from in 0..subgraph.nodes.len() {
+            let original = self.index[&subgraph.nodes[from]];
+            for &(to, weight) in &self.edges[original] {
+                if let Some(&to) = subgraph.index.get(&self.nodes[to]) {
+                    subgraph.edges[from].push((to, weight));
+                }
+            }
+        }
+        subgraph
+    }
+
+    /// Kahn's algorithm. On failure returns a cycle, listed in edge order
+    pub fn topological_sort(&self) -> Result<Vec<N>, Vec<N>> {
+        let mut in_degree = vec![0; self.nodes.len()];
+        for targets in &self.edges {
+            for &(to, _) in targets {
+                in_degree[to] += 1;
+            }
+        }
+
+        let mut ready: VecDeque<usize> = (0..self.nodes.len())
+            .filter(|&node| in_degree[node] == 0)
+            .collect();
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some(node) = ready.pop_front() {
+            order.push(self.nodes[node]);
+            for &(to, _) in &self.edges[node] {
+                in_degree[to] -= 1;
+                if in_degree[to] == 0 {
+                    ready.push_back(to);
+                }
+            }
+        }
+
+        if order.len() == self.nodes.len() {
+            Ok(order)
+        } else {
+            Err(self.find_cycle().expect("Kahn's algorithm stalled without a cycle"))
+        }
+    }
+
+    /// Depth-first topological sort (reverse postorder). On failure returns a
+    /// cycle, listed in edge order
+    pub fn topological_sort_dfs(&self) -> Result<Vec<N>, Vec<N>> {
+        let mut postorder = Vec::with_capacity(self.nodes.len());
+        if let Some(cycle) = self.dfs(|node| postorder.push(node)) {
+            return Err(cycle.into_iter().map(|node| self.nodes[node]).collect());
+        }
+        Ok(postorder.into_iter().rev().map(|node| self.nodes[node]).collect())
+    }
+
+    /// Some cycle in the graph, listed in edge order, if there is one
+    pub fn find_cycle(&self) -> Option<Vec<N>> {
+        self.dfs(|_| {})
+            .map(|cycle| cycle.into_iter().map(|node| self.nodes[node]).collect())
+    }
+
+    // Iterative DFS over all nodes calling `finished` in postorder, stopping
+    // at the first back edge found and returning the cycle it closes
+    fn dfs(&self, mut finished: impl FnMut(usize)) -> Option<Vec<usize>> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            Unvisited,
+            OnStack,
+            Done,
+        }
+
+        let mut marks = vec![Mark::Unvisited; self.nodes.len()];
+        for root in 0..self.nodes.len() {
+            if marks[root] != Mark::Unvisited {
+                continue;
+            }
+
+            // (node, index of the next edge to follow)
+            let mut stack = vec![(root, 0)];
+            marks[root] = Mark::OnStack;
+            while let Some((node, next_edge)) = stack.last_mut() {
+                let node = *node;
+                let Some(&(to, _)) = self.edges[node].get(*next_edge) else {
+                    stack.pop();
+                    marks[node] = Mark::Done;
+                    finished(node);
+                    continue;
+                };
+                *next_edge += 1;
+
+                match marks[to] {
+                    Mark::Unvisited => {
+                        marks[to] = Mark::OnStack;
+                        stack.push((to, 0));
+                    }
+                    Mark::OnStack => {
+                        let start = stack.iter().position(|&(node, _)| node == to).unwrap();
+                        return Some(stack[start..].iter().map(|&(node, _)| node).collect());
+                    }
+                    Mark::Done => {}
+                }
+            }
+        }
+        None
+    }
+
+    /// Nodes reachable from `start` (including itself) with their hop counts
+    pub fn bfs(&self, start: N) -> HashMap<N, usize> {
+        let mut distances = HashMap::new();
+        let Some(&start) = self.index.get(&start) else {
+            return distances;
+        };
+
+        let mut seen = vec![false; self.nodes.len()];
+        let mut queue = VecDeque::from([(start, 0)]);
+        seen[start] = true;
+        while let Some((node, distance)) = queue.pop_front() {
+            distances.insert(self.nodes[node], distance);
+            for &(to, _) in &self.edges[node] {
+                if !seen[to] {
+                    seen[to] = true;
+                    queue.push_back((to, distance + 1));
+                }
+            }
+        }
+        distances
+    }
+
+    pub fn reachable(&self, from: N, to: N) -> bool {
+        self.bfs(from).contains_key(&to)
+    }
+
+    /// Shortest weighted distances from `start` to every reachable node
+    pub fn dijkstra(&self, start: N) -> HashMap<N, u64> {
+        let mut distances = HashMap::new();
+        let Some(&start) = self.index.get(&start) else {
+            return distances;
+        };
+
+        let mut best = vec![u64::MAX; self.nodes.len()];
+        let mut queue = BinaryHeap::from([Reverse((0, start))]);
+        best[start] = 0;
+        while let Some(Reverse((distance, node))) = queue.pop() {
+            if distance > best[node] {
+                continue;
+            }
+            distances.insert(self.nodes[node], distance);
+            for &(to, weight) in &self.edges[node] {
+                let candidate = distance + weight;
+                if candidate < best[to] {
+                    best[to] = candidate;
+                    queue.push(Reverse((candidate, to)));
+                }
+            }
+        }
+        distances
+    }
+
+    /// A graph with an edge `a -> b` whenever `b` is reachable from `a` by a
+    /// non-empty path. Edge weights are 1
+    pub fn transitive_closure(&self) -> Graph<N> {
+        let mut closure = Graph {
+            nodes: self.nodes.clone(),
+            index: self.index.clone(),
+            edges: vec![Vec::new(); self.nodes.len()],
+        };
+        for from in 0..self.nodes.len() {
+            let mut seen = vec![false; self.nodes.len()];
+            let mut stack: Vec<usize> = self.edges[from].iter().map(|&(to, _)| to).collect();
+            while let Some(node) = stack.pop() {
+                if seen[node] {
+                    continue;
+                }
+                seen[node] = true;
+                closure.edges[from].push((node, 1));
+                stack.extend(self.edges[node].iter().map(|&(to, _)| to));
+            }
+        }
+        closure
+    }
+
+    /// The smallest subgraph with the same reachability, keeping edge weights.
+    /// Only unique for acyclic graphs, so a cycle is returned otherwise
+    pub fn transitive_reduction(&self) -> Result<Graph<N>, Vec<N>> {
+        if let Some(cycle) = self.find_cycle() {
+            return Err(cycle);
+        }
+
+        // An edge u -> v is redundant if v is reachable from another successor of u
+        let closure = self.transitive_closure();
+        let mut reduction = self.clone();
+        for (from, targets) in reduction.edges.iter_mut().enumerate() {
+            let indirect: HashSet<usize> = self.edges[from]
+                .iter()
+                .flat_map(|&(via, _)| closure.edges[via].iter().map(|&(to, _)| to))
+                .collect();
+            targets.retain(|(to, _)| !indirect.contains(to));
+        }
+        Ok(reduction)
+    }
+}
@@ -0,0 +1,166 @@
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// One bit per cell of a `width` x `height` grid.
+#[derive(Clone)]
+pub struct BitGrid {
+    words: Vec<u64>,
+    width: usize,
+    height: usize,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> BitGrid {
+        BitGrid {
+            words: vec![0; (width * height).div_ceil(WORD_BITS)],
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn index(&self, i: usize, j: usize) -> usize {
+        assert!(i < self.height && j < self.width, "({}, {}) out of bounds", i, j);
+        i * self.width + j
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> bool {
+        let index = self.index(i, j);
+        self.words[index / WORD_BITS] & (1 << (index % WORD_BITS)) != 0
+    }
+
+    /// Sets the bit, returning whether it was previously unset
+    pub fn insert(&mut self, i: usize, j: usize) -> bool {
+        let index = self.index(i, j);
+        let word = &mut self.words[index / WORD_BITS];
+        let mask = 1 << (index % WORD_BITS);
+        let newly_set = *word & mask == 0;
+        *word |= mask;
+        newly_set
+    }
+
+    pub fn remove(&mut self, i: usize, j: usize) {
+        let index = self.index(i, j);
+        self.words[index / WORD_BITS] &= !(1 << (index % WORD_BITS));
+    }
+
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Set cells in row-major order
+    pub fn iter_ones(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.words
+            .iter()
+            .enumerate()
+            .flat_map(|(word_idx, &word)| {
+                let mut word = word;
+                std::iter::from_fn(move || {
+                    if word == 0 {
+                        return None;
+                    }
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    Some(word_idx * WORD_BITS + bit)
+                })
+            })
+            .map(|index| (index / self.width, index % self.width))
+    }
+}
+
+/// Four bits per cell, one for each direction of travel through it. Direction
+/// indexes are `0..4`.
+#[derive(Clone)]
+pub struct DirectionalBitGrid {
+    words: Vec<u64>,
+    width: usize,
+    height: usize,
+}
+
+const CELLS_PER_WORD: usize = WORD_BITS / 4;
+
+impl DirectionalBitGrid {
+    pub fn new(width: usize, height: usize) -> DirectionalBitGrid {
+        DirectionalBitGrid {
+            words: vec![0; (width * height).div_ceil(CELLS_PER_WORD)],
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // (word index, bit offset of the cell's nibble)
+    fn locate(&self, i: usize, j: usize) -> (usize, usize) {
+        assert!(i < self.height && j < self.width, "({}, {}) out of bounds", i, j);
+        let index = i * self.width + j;
+        (index / CELLS_PER_WORD, (index % CELLS_PER_WORD) * 4)
+    }
+
+    pub fn get(&self, i: usize, j: usize, direction: usize) -> bool {
+        assert!(direction < 4);
+        let (word, offset) = self.locate(i, j);
+        self.words[word] & (1 << (offset + direction)) != 0
+    }
+
+    /// All four direction bits of a cell
+    pub fn directions(&self, i: usize, j: usize) -> u8 {
+        let (word, offset) = self.locate(i, j);
+        ((self.words[word] >> offset) & 0b1111) as u8
+    }
+
+    /// Sets the bit, returning whether it was previously unset
+    pub fn insert(&mut self, i: usize, j: usize, direction: usize) -> bool {
+        assert!(direction < 4);
+        let (word, offset) = self.locate(i, j);
+        let word = &mut self.words[word];
+        let mask = 1 << (offset + direction);
+        let newly_set = *word & mask == 0;
+        *word |= mask;
+        newly_set
+    }
+
+    pub fn remove(&mut self, i: usize, j: usize, direction: usize) {
+        assert!(direction < 4);
+        let (word, offset) = self.locate(i, j);
+        self.words[word] &= !(1 << (offset + direction));
+    }
+
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    /// Number of (cell, direction) states set
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Number of cells with at least one direction set
+    pub fn count_cells(&self) -> usize {
+        // Fold each nibble onto its lowest bit, then count those
+        const LOW_BITS: u64 = 0x1111_1111_1111_1111;
+        self.words
+            .iter()
+            .map(|&word| {
+                let folded = word | (word >> 1) | (word >> 2) | (word >> 3);
+                (folded & LOW_BITS).count_ones() as usize
+            })
+            .sum()
+    }
+}
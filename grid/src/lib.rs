@@ -0,0 +1,12 @@
+pub mod bitgrid;
+pub mod render;
+
+pub use bitgrid::{BitGrid, DirectionalBitGrid};
+pub use render::{ColorMode, Layer, Renderer, Style};
+
+/// A rectangular grid of characters, indexed by `(row, column)`.
+pub trait Grid {
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
+    fn cell(&self, i: usize, j: usize) -> char;
+}
@@ -0,0 +1,369 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    io::{self, IsTerminal},
+};
+
+use crate::Grid;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    Bold,
+}
+
+impl Style {
+    fn ansi_code(self) -> &'static str {
+        match self {
+            Style::Red => "31",
+            Style::Green => "32",
+            Style::Yellow => "33",
+            Style::Blue => "34",
+            Style::Magenta => "35",
+            Style::Cyan => "36",
+            Style::Bold => "1",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Color only when stdout is a terminal and `NO_COLOR` is unset
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    fn enabled(self) -> bool {
+        match self {
+            ColorMode::Auto => io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+}
+
+/// A set of cells drawn in one style, listed in the legend under `label`.
+pub struct Layer {
+    label: String,
+    style: Style,
+    // Replaces the cell's character when rendering without color, since the
+    // style alone would be invisible
+    ascii: Option<char>,
+    cells: Vec<(usize, usize)>,
+}
+
+impl Layer {
+    pub fn new(label: &str, style: Style) -> Layer {
+        Layer {
+            label: label.to_string(),
+            style,
+            ascii: None,
+            cells: Vec::new(),
+        }
+    }
+
+    pub fn ascii(mut self, glyph: char) -> Layer {
+        self.ascii = Some(glyph);
+        self
+    }
+
+    pub fn cells(mut self, cells: impl IntoIterator<Item = (usize, usize)>) -> Layer {
+        self.cells.extend(cells);
+        self
+    }
+
+    pub fn push(&mut self, cell: (usize, usize)) {
+        self.cells.push(cell);
+    }
+}
+
+struct Path {
+    label: String,
+    style: Style,
+    cells: Vec<(usize, usize)>,
+}
+
+// Orientation bits for path glyphs
+const VERTICAL: u8 = 0b01;
+const HORIZONTAL: u8 = 0b10;
+
+/// Renders a [`Grid`] with highlighted layers and path overlays, cropped to a
+/// viewport around a focus point.
+pub struct Renderer<'a, G: Grid> {
+    grid: &'a G,
+    layers: Vec<Layer>,
+    paths: Vec<Path>,
+    focus: Option<(usize, usize)>,
+    viewport: Option<(usize, usize)>,
+    color: ColorMode,
+    dim_unmarked: bool,
+    legend: bool,
+}
+
+impl<'a, G: Grid> Renderer<'a, G> {
+    pub fn new(grid: &'a G) -> Renderer<'a, G> {
+        Renderer {
+            grid,
+            layers: Vec::new(),
+            paths: Vec::new(),
+            focus: None,
+            viewport: None,
+            color: ColorMode::Auto,
+            dim_unmarked: false,
+            legend: true,
+        }
+    }
+
+    /// Later layers are drawn over earlier ones
+    pub fn layer(mut self, layer: Layer) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
+    /// Draws `cells` (in visiting order) as a line of `|`, `-` and `+`
+    pub fn path(mut self, label: &str, style: Style, cells: Vec<(usize, usize)>) -> Self {
+        self.paths.push(Path {
+            label: label.to_string(),
+            style,
+            cells,
+        });
+        self
+    }
+
+    pub fn focus(mut self, focus: (usize, usize)) -> Self {
+        self.focus = Some(focus);
+        self
+    }
+
+    /// Viewport size as (width, height). Defaults to the terminal size if
+    /// `COLUMNS` and `LINES` are set, as the OS isn't asked, and otherwise to
+    /// no cropping at all
+    pub fn viewport(mut self, width: usize, height: usize) -> Self {
+        self.viewport = Some((width.max(1), height.max(1)));
+        self
+    }
+
+    pub fn color(mut self, color: ColorMode) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Fade out (or replace with `.` in plain mode) cells not in any layer or path
+    pub fn dim_unmarked(mut self, dim: bool) -> Self {
+        self.dim_unmarked = dim;
+        self
+    }
+
+    pub fn legend(mut self, legend: bool) -> Self {
+        self.legend = legend;
+        self
+    }
+
+    /// Applies `--focus I,J`, `--viewport WxH` and `--color auto|always|never`
+    pub fn apply_args(mut self, args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut args = args.into_iter();
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}", flag))?;
+            match flag.as_str() {
+                "--focus" => {
+                    let (i, j) = parse_pair(&value, ',')?;
+                    self = self.focus((i, j));
+                }
+                "--viewport" => {
+                    let (width, height) = parse_pair(&value, 'x')?;
+                    self = self.viewport(width, height);
+                }
+                "--color" => {
+                    self = self.color(match value.as_str() {
+                        "auto" => ColorMode::Auto,
+                        "always" => ColorMode::Always,
+                        "never" => ColorMode::Never,
+                        _ => return Err(format!("Unknown color mode: {}", value)),
+                    });
+                }
+                _ => return Err(format!("Unknown render option: {}", flag)),
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn render(&self) -> String {
+        let color = self.color.enabled();
+        let (rows, cols) = self.visible_area();
+
+        // Path glyphs first, then layers on top. Each marked cell keeps the
+        // style of whatever was drawn last
+        let mut marks: HashMap<(usize, usize), (char, Style)> = HashMap::new();
+        for path in &self.paths {
+            for (cell, glyph) in path_glyphs(&path.cells) {
+                marks.insert(cell, (glyph, path.style));
+            }
+        }
+        for layer in &self.layers {
+            for &cell in &layer.cells {
+                let glyph = match (color, layer.ascii) {
+                    (false, Some(ascii)) => ascii,
+                    _ => marks
+                        .get(&cell)
+                        .map_or_else(|| self.grid.cell(cell.0, cell.1), |(glyph, _)| *glyph),
+                };
+                marks.insert(cell, (glyph, layer.style));
+            }
+        }
+
+        let mut out = String::new();
+        for i in rows.0..rows.1 {
+            for j in cols.0..cols.1 {
+                match marks.get(&(i, j)) {
+                    Some((glyph, style)) if color => {
+                        write!(out, "\x1b[{}m{}\x1b[0m", style.ansi_code(), glyph).unwrap()
+                    }
+                    Some((glyph, _)) => out.push(*glyph),
+                    None if self.dim_unmarked && color => {
+                        write!(out, "\x1b[2m{}\x1b[0m", self.grid.cell(i, j)).unwrap()
+                    }
+                    None if self.dim_unmarked => out.push('.'),
+                    None => out.push(self.grid.cell(i, j)),
+                }
+            }
+            out.push('\n');
+        }
+
+        if self.legend {
+            self.write_legend(&mut out, color, rows, cols);
+        }
+        out
+    }
+
+    fn write_legend(
+        &self,
+        out: &mut String,
+        color: bool,
+        rows: (usize, usize),
+        cols: (usize, usize),
+    ) {
+        let entries = self
+            .paths
+            .iter()
+            .map(|path| (path.label.as_str(), path.style, '+'))
+            .chain(self.layers.iter().map(|layer| {
+                (
+                    layer.label.as_str(),
+                    layer.style,
+                    layer.ascii.unwrap_or('*'),
+                )
+            }))
+            .filter(|(label, _, _)| !label.is_empty());
+        for (label, style, ascii) in entries {
+            if color {
+                writeln!(out, "\x1b[{}m██\x1b[0m {}", style.ansi_code(), label).unwrap();
+            } else {
+                writeln!(out, "{} {}", ascii, label).unwrap();
+            }
+        }
+
+        if rows != (0, self.grid.height()) || cols != (0, self.grid.width()) {
+            writeln!(
+                out,
+                "rows {}..{} cols {}..{} of {}x{}",
+                rows.0,
+                rows.1,
+                cols.0,
+                cols.1,
+                self.grid.width(),
+                self.grid.height()
+            )
+            .unwrap();
+        }
+    }
+
+    // Half-open (start, end) row and column ranges to draw
+    fn visible_area(&self) -> ((usize, usize), (usize, usize)) {
+        // Output that isn't going to a terminal, or one of unknown size, is
+        // never cropped by default
+        let Some((view_width, view_height)) = self
+            .viewport
+            .or_else(|| io::stdout().is_terminal().then(terminal_size).flatten())
+        else {
+            return ((0, self.grid.height()), (0, self.grid.width()));
+        };
+        let focus = self
+            .focus
+            .unwrap_or((self.grid.height() / 2, self.grid.width() / 2));
+        (
+            crop(self.grid.height(), view_height, focus.0),
+            crop(self.grid.width(), view_width, focus.1),
+        )
+    }
+}
+
+fn parse_pair(value: &str, separator: char) -> Result<(usize, usize), String> {
+    let invalid = || {
+        format!(
+            "Expected two numbers separated by '{}': {}",
+            separator, value
+        )
+    };
+    let (a, b) = value.split_once(separator).ok_or_else(invalid)?;
+    Ok((
+        a.trim().parse().map_err(|_| invalid())?,
+        b.trim().parse().map_err(|_| invalid())?,
+    ))
+}
+
+// Window of `view` cells out of `len`, centered on `focus` where possible
+fn crop(len: usize, view: usize, focus: usize) -> (usize, usize) {
+    if len <= view {
+        return (0, len);
+    }
+    let start = focus.saturating_sub(view / 2).min(len - view);
+    (start, start + view)
+}
+
+fn path_glyphs(cells: &[(usize, usize)]) -> HashMap<(usize, usize), char> {
+    let mut orientation: HashMap<(usize, usize), u8> = HashMap::new();
+    for (idx, &cell) in cells.iter().enumerate() {
+        let neighbors = [idx.checked_sub(1), Some(idx + 1)];
+        let mut bits = 0;
+        for other in neighbors.into_iter().flatten().filter_map(|k| cells.get(k)) {
+            bits |= if other.1 == cell.1 {
+                VERTICAL
+            } else {
+                HORIZONTAL
+            };
+        }
+        *orientation.entry(cell).or_insert(0) |= bits;
+    }
+
+    orientation
+        .into_iter()
+        .map(|(cell, bits)| {
+            let glyph = match bits {
+                VERTICAL => '|',
+                HORIZONTAL => '-',
+                _ => '+',
+            };
+            (cell, glyph)
+        })
+        .collect()
+}
+
+// The terminal size from $COLUMNS/$LINES, leaving a few rows for the legend.
+// Shells don't usually export these, so it is often unknown
+fn terminal_size() -> Option<(usize, usize)> {
+    let from_env = |name: &str| {
+        std::env::var(name)
+            .ok()
+            .and_then(|value| value.parse::<usize>().ok())
+    };
+    Some((from_env("COLUMNS")?, from_env("LINES")?.saturating_sub(4)))
+}
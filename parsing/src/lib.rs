@@ -0,0 +1,262 @@
+//! Helpers for the input shapes that keep coming up: integer lists, `a|b`
+//! pairs, `label: x y z` lines and blank-line separated sections.
+//!
+//! Everything works on a [`Cursor`], which tracks the line and column it is at
+//! so that errors point at the offending text.
+
+use std::{error::Error, fmt, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based
+    pub line: usize,
+    /// 1-based, in characters
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    /// Moves an error from a single-line parse (always line 1) to `line`
+    pub fn at_line(mut self, line: usize) -> ParseError {
+        self.line = line;
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+/// Primitive integer types, so numbers of any width can be parsed the same way
+pub trait Integer: FromStr + Copy {
+    const SIGNED: bool;
+}
+
+macro_rules! impl_integer {
+    ($signed:expr => $($t:ty),*) => {
+        $(impl Integer for $t {
+            const SIGNED: bool = $signed;
+        })*
+    };
+}
+
+impl_integer!(true => i8, i16, i32, i64, i128, isize);
+impl_integer!(false => u8, u16, u32, u64, u128, usize);
+
+#[derive(Clone, Copy)]
+pub enum Separator {
+    /// One or more spaces or tabs
+    Whitespace,
+    /// Exactly this character
+    Char(char),
+}
+
+#[derive(Clone)]
+pub struct Cursor<'a> {
+    rest: &'a str,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(text: &'a str) -> Cursor<'a> {
+        Cursor {
+            rest: text,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    pub fn rest(&self) -> &'a str {
+        self.rest
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rest.is_empty()
+    }
+
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+
+    fn advance(&mut self, bytes: usize) {
+        for c in self.rest[..bytes].chars() {
+            if c == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        self.rest = &self.rest[bytes..];
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let len = self
+            .rest
+            .find(|c| !predicate(c))
+            .unwrap_or(self.rest.len());
+        let taken = &self.rest[..len];
+        self.advance(len);
+        taken
+    }
+
+    /// Skips spaces and tabs, but not newlines
+    pub fn skip_whitespace(&mut self) {
+        self.take_while(|c| c == ' ' || c == '\t');
+    }
+
+    pub fn literal(&mut self, literal: &str) -> Result<(), ParseError> {
+        if self.rest.starts_with(literal) {
+            self.advance(literal.len());
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {:?}", literal)))
+        }
+    }
+
+    pub fn separator(&mut self, separator: Separator) -> Result<(), ParseError> {
+        match separator {
+            Separator::Whitespace => {
+                if !self.rest.starts_with([' ', '\t']) {
+                    return Err(self.error("expected whitespace"));
+                }
+                self.skip_whitespace();
+                Ok(())
+            }
+            Separator::Char(c) => self.literal(c.encode_utf8(&mut [0; 4])),
+        }
+    }
+
+    /// An optionally signed run of digits that fits in `T`
+    pub fn integer<T: Integer>(&mut self) -> Result<T, ParseError> {
+        let start = self.clone();
+        let sign_len = match self.rest.as_bytes().first() {
+            Some(b'-') if T::SIGNED => 1,
+            Some(b'+') => 1,
+            _ => 0,
+        };
+        let digits_len = self.rest[sign_len..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.rest.len() - sign_len);
+        if digits_len == 0 {
+            return Err(self.error("expected integer"));
+        }
+
+        let token = &self.rest[..sign_len + digits_len];
+        let value = token.parse().map_err(|_| {
+            start.error(format!(
+                "{} is out of range for {}",
+                token,
+                std::any::type_name::<T>()
+            ))
+        })?;
+        self.advance(token.len());
+        Ok(value)
+    }
+
+    /// One or more items separated by `separator`
+    pub fn separated<T>(
+        &mut self,
+        separator: Separator,
+        mut item: impl FnMut(&mut Cursor<'a>) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut items = vec![item(self)?];
+        loop {
+            // Only continue if a separator is followed by another item, so a
+            // trailing separator is left for `end` to complain about
+            let mut lookahead = self.clone();
+            if lookahead.separator(separator).is_err() || lookahead.at_line_end() {
+                return Ok(items);
+            }
+            *self = lookahead;
+            items.push(item(self)?);
+        }
+    }
+
+    fn at_line_end(&self) -> bool {
+        self.rest.is_empty() || self.rest.starts_with(['\n', '\r'])
+    }
+
+    /// Succeeds if only whitespace is left on the line
+    pub fn end(&mut self) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        if self.at_line_end() {
+            Ok(())
+        } else {
+            Err(self.error(format!(
+                "unexpected {:?}",
+                self.rest.lines().next().unwrap_or_default()
+            )))
+        }
+    }
+}
+
+/// A cursor over each line of `text`, numbered from 1
+pub fn lines(text: &str) -> impl Iterator<Item = Cursor<'_>> {
+    text.lines().enumerate().map(|(i, line)| Cursor {
+        rest: line,
+        line: i + 1,
+        column: 1,
+    })
+}
+
+/// Groups of non-blank lines separated by one or more blank lines
+pub fn sections(text: &str) -> Vec<Vec<Cursor<'_>>> {
+    let mut sections = vec![Vec::new()];
+    for line in lines(text) {
+        if line.rest.trim().is_empty() {
+            if !sections.last().unwrap().is_empty() {
+                sections.push(Vec::new());
+            }
+        } else {
+            sections.last_mut().unwrap().push(line);
+        }
+    }
+    if sections.last().unwrap().is_empty() {
+        sections.pop();
+    }
+    sections
+}
+
+/// `1 2 3` or `1,2,3` up to the end of the line
+pub fn integer_list<T: Integer>(
+    cursor: &mut Cursor,
+    separator: Separator,
+) -> Result<Vec<T>, ParseError> {
+    cursor.skip_whitespace();
+    let list = cursor.separated(separator, Cursor::integer)?;
+    cursor.end()?;
+    Ok(list)
+}
+
+/// `a|b` (for `separator` `|`) up to the end of the line
+pub fn pair<T: Integer>(cursor: &mut Cursor, separator: char) -> Result<(T, T), ParseError> {
+    cursor.skip_whitespace();
+    let first = cursor.integer()?;
+    cursor.literal(separator.encode_utf8(&mut [0; 4]))?;
+    let second = cursor.integer()?;
+    cursor.end()?;
+    Ok((first, second))
+}
+
+/// `label: x y z`, i.e. an integer label, a `:` and a whitespace separated list
+pub fn labelled<T: Integer>(cursor: &mut Cursor) -> Result<(T, Vec<T>), ParseError> {
+    cursor.skip_whitespace();
+    let label = cursor.integer()?;
+    cursor.literal(":")?;
+    let values = integer_list(cursor, Separator::Whitespace)?;
+    Ok((label, values))
+}
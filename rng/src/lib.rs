@@ -0,0 +1,27 @@
+//! A small seeded random number generator for generating inputs and random
+//! checks, where the same seed has to give the same run.
+//!
+//! It is xorshift64: nowhere near good enough for anything that needs real
+//! randomness, but fast and dependency free.
+
+pub struct Rng(u64);
+
+impl Rng {
+    /// xorshift never leaves 0, so a seed of 0 is treated as 1
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Below `n`, with a slight bias towards small values unless `n` is a power
+    /// of two
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}